@@ -0,0 +1,74 @@
+//! Demonstrates a cap with its own surface: a chrome sphere with a rough
+//! concrete interior.
+
+use bevy::{
+    pbr::{ExtendedMaterial, OpaqueRendererMethod},
+    prelude::*,
+};
+
+use bevy_plane_cut::{CapMaterial, PlaneCutExt, PlaneCutMaterial, PlaneCutPlugin};
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(PlaneCutPlugin)
+        .add_systems(Startup, setup)
+        .add_systems(Update, rotate_things)
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<PlaneCutMaterial>>,
+) {
+    // sphere
+    commands.spawn((
+        Mesh3d(meshes.add(Sphere::new(1.0))),
+        MeshMaterial3d(materials.add(ExtendedMaterial {
+            base: StandardMaterial {
+                base_color: Color::srgb(0.9, 0.9, 0.9),
+                metallic: 1.0,
+                perceptual_roughness: 0.1,
+                opaque_render_method: OpaqueRendererMethod::Forward,
+                ..default()
+            },
+            extension: PlaneCutExt {
+                plane: Vec4::new(-1.0, 1.0, -2.0, 0.0),
+                color: Color::srgb(0.55, 0.55, 0.5),
+                cap_material: Some(CapMaterial {
+                    perceptual_roughness: 0.95,
+                    reflectance: 0.2,
+                    ..default()
+                }),
+                ..default()
+            },
+        })),
+        Transform::from_xyz(0.0, 0.5, 0.0),
+    ));
+
+    // light
+    commands.spawn((
+        DirectionalLight {
+            illuminance: 2000.0,
+            ..default()
+        },
+        Transform::from_xyz(1.0, 1.0, 1.0).looking_at(Vec3::ZERO, Vec3::Y),
+        Rotate(Dir3::Y),
+    ));
+
+    // camera
+    commands.spawn((
+        Camera3d::default(),
+        Transform::from_xyz(-2.0, 2.5, 5.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+}
+
+#[derive(Component)]
+struct Rotate(Dir3);
+
+fn rotate_things(mut q: Query<(&mut Transform, &Rotate)>, time: Res<Time>) {
+    for (mut t, r) in &mut q {
+        t.rotate_axis(r.0, time.delta_secs());
+    }
+}
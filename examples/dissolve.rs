@@ -0,0 +1,96 @@
+//! Demonstrates a dissolve: a sphere burns away from the top as the plane
+//! moves down by the shader's clock. It restarts every few seconds.
+
+use bevy::{
+    core_pipeline::bloom::Bloom,
+    pbr::{ExtendedMaterial, OpaqueRendererMethod},
+    prelude::*,
+    color::palettes::basic,
+};
+
+use bevy_plane_cut::{Dissolve, PlaneCutExt, PlaneCutMaterial, PlaneCutPlugin};
+
+/// Where the plane starts: above the sphere and its noise.
+const START: f32 = -2.5;
+/// How fast the plane moves per second.
+const SPEED: f32 = 0.4;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(PlaneCutPlugin)
+        .insert_resource(Restart(Timer::from_seconds(8.0, TimerMode::Repeating)))
+        .add_systems(Startup, setup)
+        .add_systems(Update, restart)
+        .run();
+}
+
+#[derive(Resource)]
+struct Restart(Timer);
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<PlaneCutMaterial>>,
+    time: Res<Time>,
+) {
+    // sphere
+    commands.spawn((
+        Mesh3d(meshes.add(Sphere::new(1.0))),
+        MeshMaterial3d(materials.add(ExtendedMaterial {
+            base: StandardMaterial {
+                base_color: basic::PURPLE.into(),
+                opaque_render_method: OpaqueRendererMethod::Forward,
+                ..default()
+            },
+            extension: PlaneCutExt {
+                plane: Vec4::new(0.0, -1.0, 0.0, START),
+                color: Color::linear_rgb(0.1, 0.0, 0.1),
+                dissolve: Some(Dissolve {
+                    speed: SPEED,
+                    start: time.elapsed_secs_wrapped(),
+                    ..default()
+                }),
+                ..default()
+            },
+        })),
+        Transform::from_xyz(0.0, 1.0, 0.0),
+    ));
+
+    // light
+    commands.spawn((
+        DirectionalLight {
+            illuminance: 2000.0,
+            ..default()
+        },
+        Transform::from_xyz(1.0, 1.0, 1.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+
+    // camera
+    commands.spawn((
+        Camera3d::default(),
+        Camera {
+            hdr: true,
+            ..default()
+        },
+        Bloom::NATURAL,
+        Transform::from_xyz(2.0, 2.5, 5.0).looking_at(Vec3::new(0.0, 1.0, 0.0), Vec3::Y),
+    ));
+}
+
+/// Move the plane back to the start. The shader moves it by `speed` times the
+/// time since `start` by its clock, which is Bevy's wrapped elapsed time.
+fn restart(
+    mut restart: ResMut<Restart>,
+    mut materials: ResMut<Assets<PlaneCutMaterial>>,
+    time: Res<Time>,
+) {
+    if !restart.0.tick(time.delta()).just_finished() {
+        return;
+    }
+    for (_, material) in materials.iter_mut() {
+        if let Some(dissolve) = &mut material.extension.dissolve {
+            dissolve.start = time.elapsed_secs_wrapped();
+        }
+    }
+}
@@ -0,0 +1,96 @@
+//! Demonstrates two plane cuts, each with its own color and shading. The left
+//! sphere has a shaded and an unlit cut and the right sphere two unlit cuts.
+
+use bevy::{
+    pbr::{ExtendedMaterial, OpaqueRendererMethod},
+    prelude::*,
+    color::palettes::basic,
+};
+
+use bevy_plane_cut::{double_plane::{DoublePlaneCutExt, DoublePlaneCutMaterial}, PlaneCutPlugin, Space};
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(PlaneCutPlugin)
+        .add_systems(Startup, setup)
+        .add_systems(Update, rotate_things)
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<DoublePlaneCutMaterial>>,
+) {
+    let sphere = meshes.add(Sphere::new(1.0));
+    let base = StandardMaterial {
+        base_color: basic::RED.into(),
+        // Let's use the forward renderer.
+        opaque_render_method: OpaqueRendererMethod::Forward,
+        ..default()
+    };
+
+    // sphere with a shaded and an unlit cut
+    commands.spawn((
+        Mesh3d(sphere.clone()),
+        MeshMaterial3d(materials.add(ExtendedMaterial {
+            base: base.clone(),
+            extension: DoublePlaneCutExt {
+                plane1: Vec4::new(-1.0, 1.0, -2.0, 0.0),
+                color1: Color::linear_rgb(0.0, 0.0, 0.7),
+                space1: Space::World,
+                shaded1: true,
+                plane2: Vec4::new(1.0, 0.0, 0.0, 0.2),
+                color2: Color::linear_rgb(0.0, 0.7, 0.0),
+                space2: Space::World,
+                shaded2: false,
+            },
+        })),
+        Transform::from_xyz(-1.2, 0.5, 0.0),
+    ));
+
+    // sphere with two unlit cuts
+    commands.spawn((
+        Mesh3d(sphere),
+        MeshMaterial3d(materials.add(ExtendedMaterial {
+            base,
+            extension: DoublePlaneCutExt {
+                plane1: Vec4::new(-1.0, 1.0, -2.0, 0.0),
+                color1: Color::linear_rgb(0.0, 0.0, 0.7),
+                space1: Space::Model,
+                shaded1: false,
+                plane2: Vec4::new(1.0, 0.0, 0.0, 0.2),
+                color2: Color::linear_rgb(0.0, 0.7, 0.0),
+                space2: Space::Model,
+                shaded2: false,
+            },
+        })),
+        Transform::from_xyz(1.2, 0.5, 0.0),
+    ));
+
+    // light
+    commands.spawn((
+        DirectionalLight {
+            illuminance: 2000.0,
+            ..default()
+        },
+        Transform::from_xyz(1.0, 1.0, 1.0).looking_at(Vec3::ZERO, Vec3::Y),
+        Rotate(Dir3::Y),
+    ));
+
+    // camera
+    commands.spawn((
+        Camera3d::default(),
+        Transform::from_xyz(-2.0, 3.0, 6.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+}
+
+#[derive(Component)]
+struct Rotate(Dir3);
+
+fn rotate_things(mut q: Query<(&mut Transform, &Rotate)>, time: Res<Time>) {
+    for (mut t, r) in &mut q {
+        t.rotate_axis(r.0, time.delta_secs());
+    }
+}
@@ -0,0 +1,94 @@
+//! Demonstrates dragging plane cuts with the mouse. Drag the yellow arrows to
+//! move a plane and the rings to tilt it. The sphere's cut is driven by a
+//! `PlaneCut` entity and snaps to tenths and 15 degrees. The cube's handles edit
+//! its material's plane.
+
+use bevy::{
+    pbr::{ExtendedMaterial, OpaqueRendererMethod},
+    prelude::*,
+    color::palettes::basic,
+};
+
+use bevy_plane_cut::{
+    drag::{PlaneCutDragPlugin, PlaneCutDragged, PlaneCutHandle},
+    follow::PlaneCut,
+    PlaneCutExt, PlaneCutMaterial, PlaneCutPlugin,
+};
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(PlaneCutPlugin)
+        .add_plugins(PlaneCutDragPlugin)
+        .add_systems(Startup, setup)
+        .add_systems(Update, report_drags)
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<PlaneCutMaterial>>,
+) {
+    let base = StandardMaterial {
+        base_color: basic::SILVER.into(),
+        opaque_render_method: OpaqueRendererMethod::Forward,
+        ..default()
+    };
+
+    // sphere
+    let sphere_material = materials.add(ExtendedMaterial {
+        base: base.clone(),
+        extension: PlaneCutExt {
+            color: basic::RED.into(),
+            ..default()
+        },
+    });
+    commands.spawn((
+        Mesh3d(meshes.add(Sphere::new(0.8))),
+        MeshMaterial3d(sphere_material.clone()),
+        Transform::from_xyz(-1.2, 1.0, 0.0),
+    ));
+    commands.spawn((
+        Transform::from_xyz(-1.2, 1.0, 0.0),
+        PlaneCut::new(sphere_material).with_normal(Vec3::NEG_Z),
+        PlaneCutHandle::default().with_snap(0.1, 15f32.to_radians()),
+    ));
+
+    // cube
+    let cube_material = materials.add(ExtendedMaterial {
+        base,
+        extension: PlaneCutExt {
+            plane: Vec4::new(0.0, -1.0, -1.0, -1.4),
+            color: basic::AQUA.into(),
+            ..default()
+        },
+    });
+    commands.spawn((
+        Mesh3d(meshes.add(Cuboid::new(1.2, 1.2, 1.2))),
+        MeshMaterial3d(cube_material.clone()),
+        Transform::from_xyz(1.2, 1.0, 0.0),
+        PlaneCutHandle::new(cube_material).with_size(0.8),
+    ));
+
+    // light
+    commands.spawn((
+        DirectionalLight {
+            illuminance: 2000.0,
+            ..default()
+        },
+        Transform::from_xyz(1.0, 1.0, 1.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+
+    // camera
+    commands.spawn((
+        Camera3d::default(),
+        Transform::from_xyz(0.0, 3.0, 5.0).looking_at(Vec3::new(0.0, 1.0, 0.0), Vec3::Y),
+    ));
+}
+
+fn report_drags(mut dragged: EventReader<PlaneCutDragged>) {
+    for event in dragged.read() {
+        info!("{} dragged to {}", event.entity, event.plane);
+    }
+}
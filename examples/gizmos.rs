@@ -0,0 +1,102 @@
+//! Demonstrates drawing cut planes with gizmos: a world space cut, a model
+//! space cut on a spinning cube, and a screen space cut.
+
+use bevy::{
+    pbr::{ExtendedMaterial, OpaqueRendererMethod},
+    prelude::*,
+    color::palettes::basic,
+};
+
+use bevy_plane_cut::{gizmo::PlaneCutGizmoPlugin, PlaneCutExt, PlaneCutMaterial, PlaneCutPlugin, Space};
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(PlaneCutPlugin)
+        .add_plugins(PlaneCutGizmoPlugin)
+        .add_systems(Startup, setup)
+        .add_systems(Update, rotate_things)
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<PlaneCutMaterial>>,
+) {
+    let base = StandardMaterial {
+        base_color: basic::SILVER.into(),
+        opaque_render_method: OpaqueRendererMethod::Forward,
+        ..default()
+    };
+
+    // sphere
+    commands.spawn((
+        Mesh3d(meshes.add(Sphere::new(0.8))),
+        MeshMaterial3d(materials.add(ExtendedMaterial {
+            base: base.clone(),
+            extension: PlaneCutExt {
+                plane: Vec4::new(1.0, 1.0, 0.0, -1.0),
+                color: basic::RED.into(),
+                ..default()
+            },
+        })),
+        Transform::from_xyz(-2.0, 1.0, 0.0),
+    ));
+
+    // cube
+    commands.spawn((
+        Mesh3d(meshes.add(Cuboid::new(1.2, 1.2, 1.2))),
+        MeshMaterial3d(materials.add(ExtendedMaterial {
+            base: base.clone(),
+            extension: PlaneCutExt {
+                plane: Vec4::new(0.0, 0.0, -1.0, 0.0),
+                color: basic::LIME.into(),
+                space: Space::Model,
+                ..default()
+            },
+        })),
+        Transform::from_xyz(0.0, 1.0, 0.0),
+        Rotate(Dir3::Y),
+    ));
+
+    // torus
+    commands.spawn((
+        Mesh3d(meshes.add(Torus::new(0.4, 0.8))),
+        MeshMaterial3d(materials.add(ExtendedMaterial {
+            base,
+            extension: PlaneCutExt {
+                // Cut the left of the screen. The line is in pixels.
+                plane: Vec4::new(1.0, 0.0, 0.0, 800.0),
+                color: basic::AQUA.into(),
+                space: Space::Screen,
+                ..default()
+            },
+        })),
+        Transform::from_xyz(2.0, 1.0, 0.0).with_rotation(Quat::from_rotation_x(1.0)),
+    ));
+
+    // light
+    commands.spawn((
+        DirectionalLight {
+            illuminance: 2000.0,
+            ..default()
+        },
+        Transform::from_xyz(1.0, 1.0, 1.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+
+    // camera
+    commands.spawn((
+        Camera3d::default(),
+        Transform::from_xyz(0.0, 3.0, 6.0).looking_at(Vec3::new(0.0, 1.0, 0.0), Vec3::Y),
+    ));
+}
+
+#[derive(Component)]
+struct Rotate(Dir3);
+
+fn rotate_things(mut q: Query<(&mut Transform, &Rotate)>, time: Res<Time>) {
+    for (mut t, r) in &mut q {
+        t.rotate_axis(r.0, time.delta_secs() * 0.5);
+    }
+}
@@ -0,0 +1,81 @@
+//! Demonstrates a sphere cut into a dodecahedron by twelve planes, more than
+//! `PLANE_MAX`. Needs storage buffers; otherwise only six planes are used.
+
+use bevy::{
+    pbr::{ExtendedMaterial, OpaqueRendererMethod},
+    prelude::*,
+    color::palettes::basic,
+};
+
+use bevy_plane_cut::{nplane::{NPlaneCutExt, NPlaneCutMaterial}, PlaneCutPlugin, Space};
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(PlaneCutPlugin)
+        .add_systems(Startup, setup)
+        .add_systems(Update, rotate_things)
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<NPlaneCutMaterial>>,
+) {
+    // The face normals of a dodecahedron are the vertices of an icosahedron.
+    let phi = (1.0 + 5.0f32.sqrt()) / 2.0;
+    let mut planes_and_colors = Vec::new();
+    for (a, b) in [(1.0, 1.0), (1.0, -1.0), (-1.0, 1.0), (-1.0, -1.0)] {
+        for v in [Vec3::new(0.0, a, b * phi), Vec3::new(a, b * phi, 0.0), Vec3::new(b * phi, 0.0, a)] {
+            // Keep the inside of each face.
+            let n = -v.normalize();
+            let color = Color::linear_rgb(n.x.abs(), n.y.abs(), n.z.abs());
+            planes_and_colors.push((n.extend(-0.8), color));
+        }
+    }
+    // sphere
+    commands.spawn((
+        Mesh3d(meshes.add(Sphere::new(1.0))),
+        MeshMaterial3d(materials.add(ExtendedMaterial {
+                base: StandardMaterial {
+                    base_color: basic::RED.into(),
+                    // Let's use the forward renderer.
+                    opaque_render_method: OpaqueRendererMethod::Forward,
+                    ..default()
+                },
+                extension: NPlaneCutExt {
+                    planes_and_colors,
+                    shaded: true,
+                    space: Space::Model,
+                    ..default()
+                },
+        })),
+        Transform::from_xyz(0.0, 0.5, 0.0),
+    ));
+
+    // light
+    commands.spawn((
+        DirectionalLight {
+            illuminance: 2000.0,
+            ..default()
+        },
+        Transform::from_xyz(1.0, 1.0, 1.0).looking_at(Vec3::ZERO, Vec3::Y),
+        Rotate(Dir3::Y),
+    ));
+
+    // camera
+    commands.spawn((
+        Camera3d::default(),
+        Transform::from_xyz(-2.0, 2.5, 5.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+}
+
+#[derive(Component)]
+struct Rotate(Dir3);
+
+fn rotate_things(mut q: Query<(&mut Transform, &Rotate)>, time: Res<Time>) {
+    for (mut t, r) in &mut q {
+        t.rotate_axis(r.0, time.delta_secs());
+    }
+}
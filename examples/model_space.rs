@@ -0,0 +1,75 @@
+//! Demonstrates a model space plane cut shared by several spinning objects.
+
+use bevy::{
+    pbr::{ExtendedMaterial, OpaqueRendererMethod},
+    prelude::*,
+    color::palettes::basic,
+};
+
+use bevy_plane_cut::{PlaneCutExt, PlaneCutMaterial, PlaneCutPlugin, Space};
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(PlaneCutPlugin)
+        .add_systems(Startup, setup)
+        .add_systems(Update, rotate_things)
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<PlaneCutMaterial>>,
+) {
+    // One material for every object. The cut is in each mesh's local space, so
+    // it turns with the object.
+    let handle = materials.add(ExtendedMaterial {
+        base: StandardMaterial {
+            base_color: basic::RED.into(),
+            opaque_render_method: OpaqueRendererMethod::Forward,
+            ..default()
+        },
+        extension: PlaneCutExt {
+            plane: Vec4::new(0.0, 1.0, 1.0, 0.0),
+            color: Color::linear_rgb(0.0, 0.0, 0.7),
+            shaded: true,
+            space: Space::Model,
+            ..default()
+        },
+    });
+    let cube = meshes.add(Cuboid::new(1.0, 1.0, 1.0));
+    for (i, axis) in [Dir3::X, Dir3::Y, Dir3::Z].into_iter().enumerate() {
+        commands.spawn((
+            Mesh3d(cube.clone()),
+            MeshMaterial3d(handle.clone()),
+            Transform::from_xyz(2.0 * (i as f32 - 1.0), 0.5, 0.0)
+                .with_scale(Vec3::splat(0.5 + 0.25 * i as f32)),
+            Rotate(axis),
+        ));
+    }
+
+    // light
+    commands.spawn((
+        DirectionalLight {
+            illuminance: 2000.0,
+            ..default()
+        },
+        Transform::from_xyz(1.0, 1.0, 1.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+
+    // camera
+    commands.spawn((
+        Camera3d::default(),
+        Transform::from_xyz(0.0, 2.5, 6.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+}
+
+#[derive(Component)]
+struct Rotate(Dir3);
+
+fn rotate_things(mut q: Query<(&mut Transform, &Rotate)>, time: Res<Time>) {
+    for (mut t, r) in &mut q {
+        t.rotate_axis(r.0, time.delta_secs());
+    }
+}
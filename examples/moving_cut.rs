@@ -6,20 +6,17 @@ use bevy::{
     color::palettes::basic,
 };
 
-use bevy_plane_cut::{PlaneCutExt, PlaneCutMaterial, PlaneCutPlugin, Space};
+use bevy_plane_cut::{follow::PlaneCut, PlaneCutExt, PlaneCutMaterial, PlaneCutPlugin, Space};
 
 fn main() {
     App::new()
         .add_plugins(DefaultPlugins)
         .add_plugins(PlaneCutPlugin)
         .add_systems(Startup, setup)
-        .add_systems(Update, (rotate_things, translate_things, update_plane))
+        .add_systems(Update, (rotate_things, translate_things))
         .run();
 }
 
-#[derive(Component)]
-struct Plane(Handle<PlaneCutMaterial>);
-
 fn setup(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
@@ -36,11 +33,12 @@ fn setup(
             color: Color::linear_rgb(0.0, 0.0, 0.7),
             shaded: true,
             space: Space::World,
+            ..default()
         },
     });
     commands.spawn((
         Transform::default(),
-        Plane(handle.clone()),
+        PlaneCut::new(handle.clone()).with_normal(Vec3::NEG_X),
         // Rotate(Vec3::new(1.0, 1.0, 0.0))
         Translate(Vec3::new(1.0, 0.0, 0.0)),
     ));
@@ -68,21 +66,6 @@ fn setup(
     ));
 }
 
-fn update_plane(
-    q: Query<(&GlobalTransform, &Plane)>,
-    mut materials: ResMut<Assets<PlaneCutMaterial>>,
-) {
-    for (t, p) in &q {
-        let Some(m) = materials.get_mut(&p.0) else {
-            continue;
-        };
-        trace!("Updating plane");
-        let normal = t.left();
-        let w = normal.dot(t.translation());
-        m.extension.plane = (*normal, w).into();
-    }
-}
-
 #[derive(Component)]
 struct Translate(Vec3);
 
@@ -0,0 +1,113 @@
+//! Demonstrates picking cut meshes where they are drawn. Hover a cut mesh to
+//! highlight it and click to log whether the surface or a cap was hit. Clicks
+//! through the cut away quarter of the sphere hit the cube behind it.
+
+use bevy::{
+    pbr::{ExtendedMaterial, OpaqueRendererMethod},
+    prelude::*,
+    color::palettes::basic,
+};
+
+use bevy_plane_cut::{
+    nplane::{Combine, NPlaneCutExt, NPlaneCutMaterial},
+    pick::{PlaneCutCapHits, PlaneCutPickingPlugin},
+    PlaneCutExt, PlaneCutMaterial, PlaneCutPlugin,
+};
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(PlaneCutPlugin)
+        // Instead of `MeshPickingPlugin`.
+        .add_plugins(PlaneCutPickingPlugin)
+        .add_systems(Startup, setup)
+        .add_observer(log_click)
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<PlaneCutMaterial>>,
+    mut nplane_materials: ResMut<Assets<NPlaneCutMaterial>>,
+) {
+    let base = StandardMaterial {
+        base_color: basic::SILVER.into(),
+        opaque_render_method: OpaqueRendererMethod::Forward,
+        ..default()
+    };
+
+    // sphere
+    commands
+        .spawn((
+            Name::new("sphere"),
+            Mesh3d(meshes.add(Sphere::new(1.0))),
+            MeshMaterial3d(nplane_materials.add(ExtendedMaterial {
+                base: base.clone(),
+                extension: NPlaneCutExt {
+                    planes_and_colors: vec![
+                        (Vec4::new(-1.0, 0.0, 0.0, 0.0), basic::LIME.into()),
+                        (Vec4::new(0.0, 0.0, -1.0, 0.0), basic::BLUE.into()),
+                    ],
+                    combine: Combine::Union,
+                    ..default()
+                },
+            })),
+            Transform::from_xyz(0.0, 1.0, 0.0),
+        ))
+        .observe(highlight::<Pointer<Over>>(1.5))
+        .observe(highlight::<Pointer<Out>>(1.0));
+
+    // cube
+    commands
+        .spawn((
+            Name::new("cube"),
+            Mesh3d(meshes.add(Cuboid::new(1.0, 1.0, 1.0))),
+            MeshMaterial3d(materials.add(ExtendedMaterial {
+                base,
+                extension: PlaneCutExt {
+                    plane: Vec4::new(0.0, -1.0, 0.0, -1.5),
+                    color: basic::RED.into(),
+                    ..default()
+                },
+            })),
+            Transform::from_xyz(-0.6, 1.0, -2.0),
+        ))
+        .observe(highlight::<Pointer<Over>>(1.5))
+        .observe(highlight::<Pointer<Out>>(1.0));
+
+    // light
+    commands.spawn((
+        DirectionalLight {
+            illuminance: 2000.0,
+            ..default()
+        },
+        Transform::from_xyz(1.0, 1.0, 1.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+
+    // camera
+    commands.spawn((
+        Camera3d::default(),
+        Transform::from_xyz(3.0, 2.5, 4.0).looking_at(Vec3::new(0.0, 1.0, 0.0), Vec3::Y),
+    ));
+}
+
+/// Scale an entity when an event is triggered on it.
+fn highlight<E: Event>(scale: f32) -> impl Fn(Trigger<E>, Query<&mut Transform>) {
+    move |trigger, mut transforms| {
+        if let Ok(mut transform) = transforms.get_mut(trigger.target()) {
+            transform.scale = Vec3::splat(scale);
+        }
+    }
+}
+
+fn log_click(trigger: Trigger<Pointer<Click>>, names: Query<&Name>, cap_hits: Res<PlaneCutCapHits>) {
+    let event = trigger.event();
+    let Ok(name) = names.get(event.target) else {
+        return;
+    };
+    match cap_hits.get(event.pointer_id, event.target) {
+        Some(cap) => info!("Clicked the cap of plane {} of the {name} at {}", cap.plane_index, cap.position),
+        None => info!("Clicked the surface of the {name}"),
+    }
+}
@@ -0,0 +1,83 @@
+//! Demonstrates a glowing edge on a plane cut sweeping across a sphere.
+
+use bevy::{
+    core_pipeline::bloom::Bloom,
+    pbr::{ExtendedMaterial, OpaqueRendererMethod},
+    prelude::*,
+    color::palettes::basic,
+};
+
+use bevy_plane_cut::{follow::PlaneCut, CutEdge, PlaneCutExt, PlaneCutMaterial, PlaneCutPlugin};
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(PlaneCutPlugin)
+        .add_systems(Startup, setup)
+        .add_systems(Update, translate_things)
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<PlaneCutMaterial>>,
+) {
+    let handle = materials.add(ExtendedMaterial {
+        base: StandardMaterial {
+            base_color: basic::RED.into(),
+            opaque_render_method: OpaqueRendererMethod::Forward,
+            ..Default::default()
+        },
+        extension: PlaneCutExt {
+            color: Color::linear_rgb(0.0, 0.0, 0.7),
+            edge: Some(CutEdge {
+                width: 0.1,
+                // Bright enough to bloom.
+                color: Color::linear_rgb(0.0, 4.0, 8.0),
+                falloff: 2.0,
+            }),
+            ..default()
+        },
+    });
+    commands.spawn((
+        Transform::default(),
+        PlaneCut::new(handle.clone()).with_normal(Vec3::NEG_Y),
+        Translate(Vec3::Y),
+    ));
+    // sphere
+    commands.spawn((
+        Mesh3d(meshes.add(Sphere::new(1.0))),
+        MeshMaterial3d(handle),
+        Transform::default(),
+    ));
+
+    // light
+    commands.spawn((
+        DirectionalLight {
+            illuminance: 2000.0,
+            ..default()
+        },
+        Transform::from_xyz(1.0, 1.0, 1.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+
+    // camera
+    commands.spawn((
+        Camera3d::default(),
+        Camera {
+            hdr: true,
+            ..default()
+        },
+        Bloom::NATURAL,
+        Transform::from_xyz(2.0, 2.5, 5.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+}
+
+#[derive(Component)]
+struct Translate(Vec3);
+
+fn translate_things(mut q: Query<(&mut Transform, &Translate)>, time: Res<Time>) {
+    for (mut t, r) in &mut q {
+        t.translation = time.elapsed_secs().sin() * r.0;
+    }
+}
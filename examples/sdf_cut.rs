@@ -0,0 +1,75 @@
+//! Demonstrates a signed distance field cut: a smooth, rounded hole in a slab
+//! of terrain.
+
+use bevy::{
+    pbr::{ExtendedMaterial, OpaqueRendererMethod},
+    prelude::*,
+    color::palettes::basic,
+};
+
+use bevy_plane_cut::{
+    sdf::{SdfCutExt, SdfCutMaterial, SdfShape},
+    PlaneCutPlugin,
+};
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(PlaneCutPlugin)
+        .add_systems(Startup, setup)
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<SdfCutMaterial>>,
+) {
+    // terrain
+    commands.spawn((
+        Mesh3d(meshes.add(Cuboid::new(4.0, 1.0, 4.0))),
+        MeshMaterial3d(materials.add(ExtendedMaterial {
+            base: StandardMaterial {
+                base_color: basic::GREEN.into(),
+                opaque_render_method: OpaqueRendererMethod::Forward,
+                ..default()
+            },
+            extension: SdfCutExt {
+                // Two spheres and a tunnel blend into one smooth hole.
+                shapes: vec![
+                    SdfShape::Sphere {
+                        center: Vec3::new(0.0, 0.5, 0.0),
+                        radius: 0.8,
+                    },
+                    SdfShape::Sphere {
+                        center: Vec3::new(0.8, 0.5, 0.6),
+                        radius: 0.5,
+                    },
+                    SdfShape::Capsule {
+                        start: Vec3::new(0.0, 0.0, 0.0),
+                        end: Vec3::new(-2.5, -0.1, 0.5),
+                        radius: 0.25,
+                    },
+                ],
+                smoothness: 0.4,
+                color: Color::linear_rgb(0.3, 0.2, 0.1),
+                ..default()
+            },
+        })),
+    ));
+
+    // light
+    commands.spawn((
+        DirectionalLight {
+            illuminance: 2000.0,
+            ..default()
+        },
+        Transform::from_xyz(1.0, 1.0, 1.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+
+    // camera
+    commands.spawn((
+        Camera3d::default(),
+        Transform::from_xyz(0.0, 2.5, 6.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+}
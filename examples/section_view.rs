@@ -0,0 +1,106 @@
+//! Demonstrates hatched caps like the section views of engineering drawings:
+//! a quarter cutaway of a sphere with a different pattern on each cap and a
+//! cube with dotted caps.
+
+use bevy::{
+    pbr::{ExtendedMaterial, OpaqueRendererMethod},
+    prelude::*,
+    color::palettes::basic,
+};
+
+use bevy_plane_cut::{
+    nplane::{Combine, NPlaneCutExt, NPlaneCutMaterial},
+    CapPattern, Hatch, PlaneCutExt, PlaneCutMaterial, PlaneCutPlugin, Space,
+};
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(PlaneCutPlugin)
+        .add_systems(Startup, setup)
+        .add_systems(Update, rotate_things)
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut nplane_materials: ResMut<Assets<NPlaneCutMaterial>>,
+    mut materials: ResMut<Assets<PlaneCutMaterial>>,
+) {
+    let cap_color = Color::linear_rgb(0.8, 0.8, 0.7);
+    // sphere
+    commands.spawn((
+        Mesh3d(meshes.add(Sphere::new(1.0))),
+        MeshMaterial3d(nplane_materials.add(ExtendedMaterial {
+            base: StandardMaterial {
+                base_color: basic::RED.into(),
+                opaque_render_method: OpaqueRendererMethod::Forward,
+                ..default()
+            },
+            extension: NPlaneCutExt {
+                planes_and_colors: vec![
+                    (Vec4::new(-1.0, 0.0, 0.0, 0.0), cap_color),
+                    (Vec4::new(0.0, 0.0, -1.0, 0.0), cap_color),
+                ],
+                combine: Combine::Union,
+                patterns: vec![
+                    CapPattern::new(Hatch::Lines),
+                    CapPattern::new(Hatch::Crosshatch),
+                ],
+                ..default()
+            },
+        })),
+        Transform::from_xyz(-1.2, 0.5, 0.0),
+    ));
+
+    // cube
+    commands.spawn((
+        Mesh3d(meshes.add(Cuboid::new(1.5, 1.5, 1.5))),
+        MeshMaterial3d(materials.add(ExtendedMaterial {
+            base: StandardMaterial {
+                base_color: basic::BLUE.into(),
+                opaque_render_method: OpaqueRendererMethod::Forward,
+                ..default()
+            },
+            extension: PlaneCutExt {
+                plane: Vec4::new(-1.0, 1.0, -1.0, 0.0),
+                color: cap_color,
+                space: Space::Model,
+                pattern: CapPattern {
+                    hatch: Hatch::Dots,
+                    spacing: 0.15,
+                    line_width: 0.06,
+                    ..default()
+                },
+                ..default()
+            },
+        })),
+        Transform::from_xyz(1.2, 0.5, 0.0),
+        Rotate(Dir3::Y),
+    ));
+
+    // light
+    commands.spawn((
+        DirectionalLight {
+            illuminance: 2000.0,
+            ..default()
+        },
+        Transform::from_xyz(1.0, 1.0, 1.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+
+    // camera
+    commands.spawn((
+        Camera3d::default(),
+        Transform::from_xyz(3.0, 2.5, 5.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+}
+
+#[derive(Component)]
+struct Rotate(Dir3);
+
+fn rotate_things(mut q: Query<(&mut Transform, &Rotate)>, time: Res<Time>) {
+    for (mut t, r) in &mut q {
+        t.rotate_axis(r.0, time.delta_secs() * 0.5);
+    }
+}
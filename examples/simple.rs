@@ -37,6 +37,7 @@ fn setup(
                 color: Color::linear_rgb(0.0, 0.0, 0.7),
                 shaded: true,
                 space: Space::World,
+                ..default()
             },
         })),
         Transform::from_xyz(0.0, 0.5, 0.0),
@@ -39,6 +39,7 @@ fn setup(
                     ],
                     shaded: true,
                     space: Space::World,
+                    ..default()
                 },
         })),
         Transform::from_xyz(0.0, 0.5, 0.0),
@@ -45,6 +45,7 @@ fn setup(
             color: Color::linear_rgb(0.0, 0.0, 0.7),
             shaded: true,
             space: Space::World,
+            ..default()
         },
     });
     // sphere
@@ -39,6 +39,7 @@ fn setup(
             color: Color::linear_rgb(0.0, 0.0, 0.7),
             shaded: false,
             space: Space::Screen,
+            ..default()
         },
     });
     // sphere
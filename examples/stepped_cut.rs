@@ -0,0 +1,77 @@
+//! Demonstrates a stepped cutaway of a cube with a boolean expression of
+//! plane cuts: (A ∩ B) ∪ C.
+
+use bevy::{
+    pbr::{ExtendedMaterial, OpaqueRendererMethod},
+    prelude::*,
+    color::palettes::basic,
+};
+
+use bevy_plane_cut::{csg::{CsgCutExt, CsgCutMaterial, CutExpr}, PlaneCutPlugin, Space};
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(PlaneCutPlugin)
+        .add_systems(Startup, setup)
+        .add_systems(Update, rotate_things)
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<CsgCutMaterial>>,
+) {
+    // cube
+    commands.spawn((
+        Mesh3d(meshes.add(Cuboid::new(1.5, 1.5, 1.5))),
+        MeshMaterial3d(materials.add(ExtendedMaterial {
+                base: StandardMaterial {
+                    base_color: basic::RED.into(),
+                    // Let's use the forward renderer.
+                    opaque_render_method: OpaqueRendererMethod::Forward,
+                    ..default()
+                },
+                extension: CsgCutExt {
+                    planes_and_colors: vec![
+                        // A cuts x > 0.
+                        (Vec4::new(-1.0, 0.0, 0.0, 0.0), Color::linear_rgb(0.7, 0.7, 0.0)),
+                        // B cuts y > 0.5.
+                        (Vec4::new(0.0, -1.0, 0.0, -0.5), Color::linear_rgb(0.0, 0.7, 0.0)),
+                        // C cuts z > 0.4.
+                        (Vec4::new(0.0, 0.0, -1.0, -0.4), Color::linear_rgb(0.0, 0.0, 0.7)),
+                    ],
+                    expr: (CutExpr::plane(0) & CutExpr::plane(1)) | CutExpr::plane(2),
+                    shaded: true,
+                    space: Space::World,
+                },
+        })),
+        Transform::from_xyz(0.0, 0.5, 0.0),
+    ));
+
+    // light
+    commands.spawn((
+        DirectionalLight {
+            illuminance: 2000.0,
+            ..default()
+        },
+        Transform::from_xyz(1.0, 1.0, 1.0).looking_at(Vec3::ZERO, Vec3::Y),
+        Rotate(Dir3::Y),
+    ));
+
+    // camera
+    commands.spawn((
+        Camera3d::default(),
+        Transform::from_xyz(3.0, 2.5, 5.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+}
+
+#[derive(Component)]
+struct Rotate(Dir3);
+
+fn rotate_things(mut q: Query<(&mut Transform, &Rotate)>, time: Res<Time>) {
+    for (mut t, r) in &mut q {
+        t.rotate_axis(r.0, time.delta_secs());
+    }
+}
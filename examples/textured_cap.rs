@@ -0,0 +1,105 @@
+//! Demonstrates a textured cap: a cut brick wall shows bricks inside.
+
+use bevy::{
+    asset::RenderAssetUsages,
+    pbr::{ExtendedMaterial, OpaqueRendererMethod},
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+
+use bevy_plane_cut::{CapUv, PlaneCutExt, PlaneCutMaterial, PlaneCutPlugin, Space};
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(PlaneCutPlugin)
+        .add_systems(Startup, setup)
+        .add_systems(Update, rotate_things)
+        .run();
+}
+
+/// Make a texture of two rows of bricks with mortar between them.
+fn bricks() -> Image {
+    const SIZE: u32 = 64;
+    let mut data = Vec::with_capacity((SIZE * SIZE * 4) as usize);
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let row = y / (SIZE / 2);
+            let x = (x + row * SIZE / 2) % (SIZE / 2);
+            let mortar = y % (SIZE / 2) < 3 || x < 3;
+            data.extend_from_slice(if mortar {
+                &[200, 200, 190, 255]
+            } else {
+                &[150, 60, 40, 255]
+            });
+        }
+    }
+    Image::new(
+        Extent3d {
+            width: SIZE,
+            height: SIZE,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<PlaneCutMaterial>>,
+) {
+    // wall
+    commands.spawn((
+        Mesh3d(meshes.add(Cuboid::new(3.0, 1.5, 0.5))),
+        MeshMaterial3d(materials.add(ExtendedMaterial {
+            base: StandardMaterial {
+                base_color: Color::srgb(0.6, 0.25, 0.15),
+                opaque_render_method: OpaqueRendererMethod::Forward,
+                ..default()
+            },
+            extension: PlaneCutExt {
+                plane: Vec4::new(-1.0, 0.0, -0.5, 0.0),
+                // The texture is multiplied by the color.
+                color: Color::WHITE,
+                space: Space::Model,
+                cap_texture: Some(images.add(bricks())),
+                cap_uv: CapUv {
+                    tiling: Vec2::splat(2.0),
+                    ..default()
+                },
+                ..default()
+            },
+        })),
+        Transform::from_xyz(0.0, 0.75, 0.0),
+        Rotate(Dir3::Y),
+    ));
+
+    // light
+    commands.spawn((
+        DirectionalLight {
+            illuminance: 2000.0,
+            ..default()
+        },
+        Transform::from_xyz(1.0, 1.0, 1.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+
+    // camera
+    commands.spawn((
+        Camera3d::default(),
+        Transform::from_xyz(2.0, 2.5, 5.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+}
+
+#[derive(Component)]
+struct Rotate(Dir3);
+
+fn rotate_things(mut q: Query<(&mut Transform, &Rotate)>, time: Res<Time>) {
+    for (mut t, r) in &mut q {
+        t.rotate_axis(r.0, time.delta_secs() * 0.5);
+    }
+}
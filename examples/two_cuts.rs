@@ -64,6 +64,7 @@ fn setup(
                  Color::linear_rgb(0.0, 0.0, 1.0))], // Blue color for second cut
             space: Space::World,
             shaded: true, // Make it unlit so it's more visible
+            ..default()
         },
     });
     commands.spawn((
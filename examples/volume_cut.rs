@@ -0,0 +1,99 @@
+//! Demonstrates cutting with volumes: a sphere cut by a sphere, a cube kept
+//! only inside a box, and a cylinder drilled by a capsule.
+
+use bevy::{
+    pbr::{ExtendedMaterial, OpaqueRendererMethod},
+    prelude::*,
+    color::palettes::basic,
+};
+
+use bevy_plane_cut::{
+    volume::{CutVolume, VolumeCutExt, VolumeCutMaterial},
+    PlaneCutPlugin,
+};
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(PlaneCutPlugin)
+        .add_systems(Startup, setup)
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<VolumeCutMaterial>>,
+) {
+    let base = StandardMaterial {
+        base_color: basic::RED.into(),
+        opaque_render_method: OpaqueRendererMethod::Forward,
+        ..default()
+    };
+    let objects = [
+        (
+            meshes.add(Sphere::new(0.75)),
+            Vec3::new(-2.0, 0.5, 0.0),
+            VolumeCutExt {
+                volume: CutVolume::Sphere {
+                    center: Vec3::new(-1.6, 0.9, 0.4),
+                    radius: 0.6,
+                },
+                color: Color::linear_rgb(0.7, 0.7, 0.0),
+                ..default()
+            },
+        ),
+        (
+            meshes.add(Cuboid::new(1.2, 1.2, 1.2)),
+            Vec3::new(0.0, 0.5, 0.0),
+            VolumeCutExt {
+                volume: CutVolume::Cuboid {
+                    center: Vec3::new(0.0, 0.5, 0.0),
+                    half_size: Vec3::new(0.7, 0.4, 0.4),
+                    rotation: Quat::from_rotation_y(0.5),
+                },
+                color: Color::linear_rgb(0.0, 0.7, 0.0),
+                invert: true,
+                ..default()
+            },
+        ),
+        (
+            meshes.add(Cylinder::new(0.5, 1.5)),
+            Vec3::new(2.0, 0.5, 0.0),
+            VolumeCutExt {
+                volume: CutVolume::Capsule {
+                    start: Vec3::new(1.2, 0.5, 1.0),
+                    end: Vec3::new(2.8, 0.5, -1.0),
+                    radius: 0.25,
+                },
+                color: Color::linear_rgb(0.0, 0.0, 0.7),
+                ..default()
+            },
+        ),
+    ];
+    for (mesh, translation, extension) in objects {
+        commands.spawn((
+            Mesh3d(mesh),
+            MeshMaterial3d(materials.add(ExtendedMaterial {
+                base: base.clone(),
+                extension,
+            })),
+            Transform::from_translation(translation),
+        ));
+    }
+
+    // light
+    commands.spawn((
+        DirectionalLight {
+            illuminance: 2000.0,
+            ..default()
+        },
+        Transform::from_xyz(1.0, 1.0, 1.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+
+    // camera
+    commands.spawn((
+        Camera3d::default(),
+        Transform::from_xyz(0.0, 2.5, 6.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+}
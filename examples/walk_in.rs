@@ -0,0 +1,84 @@
+//! Demonstrates walking into a cut building. The camera passes through a cut
+//! box, whose inner walls are not caps, next to a mirrored copy of it.
+
+use bevy::{
+    pbr::{ExtendedMaterial, OpaqueRendererMethod},
+    prelude::*,
+    color::palettes::basic,
+};
+
+use bevy_plane_cut::{PlaneCutExt, PlaneCutMaterial, PlaneCutPlugin, Space};
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(PlaneCutPlugin)
+        .add_systems(Startup, setup)
+        .add_systems(Update, walk)
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<PlaneCutMaterial>>,
+    mut standard_materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let handle = materials.add(ExtendedMaterial {
+        base: StandardMaterial {
+            base_color: basic::SILVER.into(),
+            opaque_render_method: OpaqueRendererMethod::Forward,
+            ..default()
+        },
+        extension: PlaneCutExt {
+            // Cut away the front half.
+            plane: Vec4::new(0.0, 0.0, -1.0, 0.0),
+            color: basic::MAROON.into(),
+            space: Space::Model,
+            ..default()
+        },
+    });
+    let building = meshes.add(Cuboid::new(3.0, 2.0, 4.0));
+
+    // building
+    commands.spawn((
+        Mesh3d(building.clone()),
+        MeshMaterial3d(handle.clone()),
+        Transform::from_xyz(-2.0, 1.0, 0.0),
+    ));
+
+    // mirrored building
+    commands.spawn((
+        Mesh3d(building),
+        MeshMaterial3d(handle),
+        Transform::from_xyz(2.0, 1.0, 0.0).with_scale(Vec3::new(-1.0, 1.0, 1.0)),
+    ));
+
+    // ground
+    commands.spawn((
+        Mesh3d(meshes.add(Plane3d::default().mesh().size(20.0, 20.0))),
+        MeshMaterial3d(standard_materials.add(Color::from(basic::GREEN))),
+    ));
+
+    // light
+    commands.spawn((
+        PointLight {
+            shadows_enabled: true,
+            ..default()
+        },
+        Transform::from_xyz(0.0, 4.0, 4.0),
+    ));
+
+    // camera
+    commands.spawn((
+        Camera3d::default(),
+        Transform::from_xyz(-2.0, 1.0, 6.0).looking_at(Vec3::new(-2.0, 1.0, 0.0), Vec3::Y),
+    ));
+}
+
+/// Walk into the building and back out.
+fn walk(mut q: Query<&mut Transform, With<Camera3d>>, time: Res<Time>) {
+    for mut t in &mut q {
+        t.translation.z = 2.0 + 4.0 * (time.elapsed_secs() * 0.3).cos();
+    }
+}
@@ -0,0 +1,100 @@
+//! Demonstrates a cut glass housing. The housing is drawn twice, once for its
+//! opaque caps and once for its transparent surface, so the caps show through
+//! the glass in front of them.
+
+use bevy::{
+    pbr::{ExtendedMaterial, OpaqueRendererMethod},
+    prelude::*,
+    color::palettes::basic,
+};
+
+use bevy_plane_cut::{CutFaces, PlaneCutExt, PlaneCutMaterial, PlaneCutPlugin};
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(PlaneCutPlugin)
+        .add_systems(Startup, setup)
+        .add_systems(Update, rotate_things)
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<PlaneCutMaterial>>,
+    mut standard_materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let cut = PlaneCutExt {
+        plane: Vec4::new(-1.0, 0.0, -1.0, 0.0),
+        color: basic::TEAL.into(),
+        ..default()
+    };
+    let housing = meshes.add(Cylinder::new(1.0, 2.0));
+
+    // housing caps
+    commands.spawn((
+        Mesh3d(housing.clone()),
+        MeshMaterial3d(materials.add(ExtendedMaterial {
+            base: StandardMaterial {
+                opaque_render_method: OpaqueRendererMethod::Forward,
+                ..default()
+            },
+            extension: PlaneCutExt {
+                faces: CutFaces::Caps,
+                ..cut.clone()
+            },
+        })),
+        Transform::from_xyz(0.0, 1.0, 0.0),
+    ));
+
+    // housing surface
+    commands.spawn((
+        Mesh3d(housing),
+        MeshMaterial3d(materials.add(ExtendedMaterial {
+            base: StandardMaterial {
+                base_color: Color::srgba(0.8, 0.9, 1.0, 0.3),
+                alpha_mode: AlphaMode::Blend,
+                perceptual_roughness: 0.1,
+                ..default()
+            },
+            extension: PlaneCutExt {
+                faces: CutFaces::Surface,
+                ..cut
+            },
+        })),
+        Transform::from_xyz(0.0, 1.0, 0.0),
+    ));
+
+    // part inside the housing
+    commands.spawn((
+        Mesh3d(meshes.add(Torus::new(0.3, 0.6))),
+        MeshMaterial3d(standard_materials.add(Color::from(basic::YELLOW))),
+        Transform::from_xyz(0.0, 1.0, 0.0),
+        Rotate(Dir3::X),
+    ));
+
+    // light
+    commands.spawn((
+        DirectionalLight {
+            illuminance: 2000.0,
+            ..default()
+        },
+        Transform::from_xyz(1.0, 1.0, 1.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+
+    // camera
+    commands.spawn((
+        Camera3d::default(),
+        Transform::from_xyz(-2.0, 2.5, 5.0).looking_at(Vec3::new(0.0, 1.0, 0.0), Vec3::Y),
+    ));
+}
+
+#[derive(Component)]
+struct Rotate(Dir3);
+
+fn rotate_things(mut q: Query<(&mut Transform, &Rotate)>, time: Res<Time>) {
+    for (mut t, r) in &mut q {
+        t.rotate_axis(r.0, time.delta_secs());
+    }
+}
@@ -0,0 +1,183 @@
+use bevy::{
+    asset::Assets,
+    ecs::{
+        entity::Entity,
+        system::{Query, Res, SystemParam},
+    },
+    math::{Vec2, Vec3},
+    pbr::MeshMaterial3d,
+    render::{camera::Camera, renderer::RenderDevice},
+    time::Time,
+    transform::components::GlobalTransform,
+};
+use super::*;
+use crate::nplane::{max_planes, NPlaneCutExt, NPlaneCutMaterial};
+
+/// Return the frag coord of a world position seen by a camera: its physical
+/// pixel and its NDC depth.
+pub(crate) fn frag_coord(
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    position: Vec3,
+) -> Option<Vec3> {
+    let rect = camera.physical_viewport_rect()?;
+    let (min, max) = (rect.min.as_vec2(), rect.max.as_vec2());
+    let ndc = camera.world_to_ndc(camera_transform, position)?;
+    let uv = Vec2::new(ndc.x + 1.0, 1.0 - ndc.y) / 2.0;
+    Some((min + uv * (max - min)).extend(ndc.z))
+}
+
+/// A cut material's extension.
+enum Cut<'a> {
+    Single(&'a PlaneCutExt),
+    NPlane(&'a NPlaneCutExt),
+}
+
+impl Cut<'_> {
+    fn space(&self) -> &Space {
+        match self {
+            Cut::Single(cut) => &cut.space,
+            Cut::NPlane(cut) => &cut.space,
+        }
+    }
+}
+
+type CutEntityData = (
+    &'static GlobalTransform,
+    Option<&'static MeshMaterial3d<PlaneCutMaterial>>,
+    Option<&'static MeshMaterial3d<NPlaneCutMaterial>>,
+);
+
+/// Ask whether an entity's [`PlaneCutMaterial`] or [`NPlaneCutMaterial`] cuts
+/// a world point on the CPU like its shader does, e.g., to hide markers in the
+/// removed half or to let projectiles pass through cut walls.
+///
+/// The answers follow the entity's current material, [`Space`], and
+/// transform and the shaders' clock. [`Space::Screen`] cuts are tested as seen
+/// by a camera, which is the active camera of the highest order unless one is
+/// given. A soft cut is tested as if it were hard. Like the shader, an
+/// [`NPlaneCutMaterial`] is only tested against the planes the device cuts
+/// with.
+///
+/// ```
+/// use bevy::prelude::*;
+/// use bevy_plane_cut::clip::CutQuery;
+///
+/// #[derive(Component)]
+/// struct Marker {
+///     building: Entity,
+/// }
+///
+/// fn hide_cut_markers(
+///     cuts: CutQuery,
+///     mut markers: Query<(&Marker, &GlobalTransform, &mut Visibility)>,
+/// ) {
+///     for (marker, transform, mut visibility) in &mut markers {
+///         *visibility = match cuts.is_clipped(marker.building, transform.translation()) {
+///             Some(true) => Visibility::Hidden,
+///             _ => Visibility::Inherited,
+///         };
+///     }
+/// }
+/// # bevy::ecs::system::assert_is_system(hide_cut_markers);
+/// ```
+#[derive(SystemParam)]
+pub struct CutQuery<'w, 's> {
+    entities: Query<'w, 's, CutEntityData>,
+    cameras: Query<'w, 's, (Entity, &'static Camera, &'static GlobalTransform)>,
+    materials: Res<'w, Assets<PlaneCutMaterial>>,
+    nplane_materials: Res<'w, Assets<NPlaneCutMaterial>>,
+    time: Res<'w, Time>,
+    render_device: Option<Res<'w, RenderDevice>>,
+}
+
+impl CutQuery<'_, '_> {
+    /// Return an entity's cut and its transform.
+    fn cut(&self, entity: Entity) -> Option<(Cut<'_>, &GlobalTransform)> {
+        let (transform, material, nplane_material) = self.entities.get(entity).ok()?;
+        let cut = match (material, nplane_material) {
+            (Some(material), _) => Cut::Single(&self.materials.get(material)?.extension),
+            (_, Some(material)) => Cut::NPlane(&self.nplane_materials.get(material)?.extension),
+            _ => return None,
+        };
+        Some((cut, transform))
+    }
+
+    /// Return the active camera of the highest order.
+    fn default_camera(&self) -> Option<Entity> {
+        self.cameras
+            .iter()
+            .filter(|(_, camera, _)| camera.is_active)
+            .max_by_key(|(_, camera, _)| camera.order)
+            .map(|(entity, _, _)| entity)
+    }
+
+    /// Return the point an entity's shader tests for a world position, seen
+    /// by `camera` or the default camera in screen space, or `None` if the
+    /// entity has no cut material or a screen space cut has no camera.
+    pub fn cut_point(
+        &self,
+        entity: Entity,
+        position: Vec3,
+        camera: Option<Entity>,
+    ) -> Option<CutPoint> {
+        let (cut, transform) = self.cut(entity)?;
+        let frag_coord = match cut.space() {
+            Space::Screen => {
+                let (_, camera, camera_transform) =
+                    self.cameras.get(camera.or_else(|| self.default_camera())?).ok()?;
+                frag_coord(camera, camera_transform, position)?
+            }
+            _ => position,
+        };
+        Some(CutPoint {
+            position,
+            transform: *transform,
+            frag_coord,
+            time: self.time.elapsed_secs_wrapped(),
+            max_planes: self.render_device.as_deref().and_then(max_planes),
+        })
+    }
+
+    /// Return whether an entity's cut material cuts a world position or
+    /// `None` if it has no cut material.
+    pub fn is_clipped(&self, entity: Entity, position: Vec3) -> Option<bool> {
+        self.is_clipped_from(entity, position, None)
+    }
+
+    /// Return whether an entity's cut material cuts a world position seen by
+    /// a camera.
+    pub fn is_clipped_from(
+        &self,
+        entity: Entity,
+        position: Vec3,
+        camera: Option<Entity>,
+    ) -> Option<bool> {
+        let point = self.cut_point(entity, position, camera)?;
+        Some(match self.cut(entity)?.0 {
+            Cut::Single(cut) => cut.is_clipped(point),
+            Cut::NPlane(cut) => cut.is_clipped(point),
+        })
+    }
+
+    /// Return how far a world position is from an entity's cut, which is
+    /// negative where it is cut, or `None` if it has no cut material.
+    pub fn distance(&self, entity: Entity, position: Vec3) -> Option<f32> {
+        self.distance_from(entity, position, None)
+    }
+
+    /// Return how far a world position is from an entity's cut seen by a
+    /// camera.
+    pub fn distance_from(
+        &self,
+        entity: Entity,
+        position: Vec3,
+        camera: Option<Entity>,
+    ) -> Option<f32> {
+        let point = self.cut_point(entity, position, camera)?;
+        Some(match self.cut(entity)?.0 {
+            Cut::Single(cut) => cut.distance(point),
+            Cut::NPlane(cut) => cut.distance(point),
+        })
+    }
+}
@@ -0,0 +1,161 @@
+use crate::slice::{parents, positions_and_indices, signed_area, Slicer};
+use bevy::{
+    math::{Vec2, Vec3, Vec4},
+    render::mesh::Mesh,
+    transform::components::GlobalTransform,
+};
+
+/// Where a plane meets a mesh.
+#[derive(Debug, Clone, Default)]
+pub struct CrossSection {
+    /// The polylines where the plane meets the mesh.
+    pub contours: Vec<Contour>,
+    /// The origin of the plane's 2D coordinates, the point on the plane closest
+    /// to the world's origin.
+    pub origin: Vec3,
+    /// The world direction of the plane's 2D x axis.
+    pub x_axis: Vec3,
+    /// The world direction of the plane's 2D y axis.
+    pub y_axis: Vec3,
+}
+
+impl CrossSection {
+    /// Return the world position of a point in the plane's 2D coordinates.
+    pub fn to_world(&self, p: Vec2) -> Vec3 {
+        self.origin + p.x * self.x_axis + p.y * self.y_axis
+    }
+
+    /// Return a world position projected into the plane's 2D coordinates.
+    pub fn to_plane(&self, p: Vec3) -> Vec2 {
+        let d = p - self.origin;
+        Vec2::new(d.dot(self.x_axis), d.dot(self.y_axis))
+    }
+}
+
+/// A polyline where a plane meets a mesh.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Contour {
+    /// The points in world space. A closed contour's last point connects to
+    /// its first.
+    pub points: Vec<Vec3>,
+    /// The points in the plane's 2D coordinates.
+    pub points_2d: Vec<Vec2>,
+    /// Is it a loop? Meshes that are not closed may have open contours.
+    pub closed: bool,
+    /// The index of the closed contour this is directly inside of, if any.
+    pub parent: Option<usize>,
+    /// How many closed contours this is inside of. Open contours are zero.
+    pub depth: usize,
+}
+
+impl Contour {
+    /// Is this a hole in its parent? Holes are at odd depths.
+    pub fn is_hole(&self) -> bool {
+        self.closed && self.depth % 2 == 1
+    }
+
+    /// Return the area a closed contour encloses not less its holes, or zero
+    /// if it is open.
+    pub fn area(&self) -> f32 {
+        if self.closed {
+            signed_area(&self.points_2d).abs()
+        } else {
+            0.0
+        }
+    }
+
+    /// Return the length of the polyline including a closed contour's last
+    /// segment.
+    pub fn length(&self) -> f32 {
+        let closing = self
+            .closed
+            .then(|| self.points.last().zip(self.points.first()))
+            .flatten();
+        self.points
+            .windows(2)
+            .map(|w| (w[0], w[1]))
+            .chain(closing.map(|(a, b)| (*a, *b)))
+            .map(|(a, b)| a.distance(b))
+            .sum()
+    }
+}
+
+/// Return where a plane meets a mesh.
+///
+/// The plane is _(nx, ny, nz, w)_ like [`PlaneCutExt::plane`](crate::PlaneCutExt::plane)
+/// in world space, and the mesh is placed in the world by its transform.
+/// Contours are joined across vertices duplicated at seams.
+///
+/// The plane's 2D coordinates look at the face the plane cuts from the side
+/// that is cut, like a section view. In them, closed contours are
+/// counterclockwise and holes clockwise.
+///
+/// Only triangle lists are supported; other topologies have no contours.
+///
+/// ```
+/// use bevy::prelude::*;
+/// use bevy_plane_cut::contour::cross_section;
+/// let mesh = Mesh::from(Cuboid::new(2.0, 2.0, 2.0));
+/// let section = cross_section(&mesh, &GlobalTransform::IDENTITY, Vec4::new(0.0, 1.0, 0.0, 0.5));
+/// assert_eq!(section.contours.len(), 1);
+/// assert!((section.contours[0].length() - 8.0).abs() < 1e-4);
+/// ```
+pub fn cross_section(mesh: &Mesh, transform: &GlobalTransform, plane: Vec4) -> CrossSection {
+    let n = plane.truncate().normalize();
+    let x_axis = n.any_orthonormal_vector();
+    let mut section = CrossSection {
+        contours: Vec::new(),
+        origin: n * plane.w / plane.truncate().length(),
+        x_axis,
+        // Seen from the cut side.
+        y_axis: x_axis.cross(n),
+    };
+    let Some((positions, indices)) = positions_and_indices(mesh, "cross_section") else {
+        return section;
+    };
+    let positions: Vec<Vec3> = positions
+        .iter()
+        .map(|p| transform.transform_point(*p))
+        .collect();
+    let mut slicer = Slicer::new(&positions, plane);
+    let segments = slicer.split(&indices).segments;
+    for (chain, closed) in slicer.chains(&segments) {
+        let points: Vec<Vec3> = chain.iter().map(|i| slicer.position(*i)).collect();
+        let points_2d = points.iter().map(|p| section.to_plane(*p)).collect();
+        section.contours.push(Contour {
+            points,
+            points_2d,
+            closed,
+            parent: None,
+            depth: 0,
+        });
+    }
+
+    let closed: Vec<usize> = (0..section.contours.len())
+        .filter(|i| section.contours[*i].closed)
+        .collect();
+    let loops: Vec<Vec<Vec2>> = closed
+        .iter()
+        .map(|i| section.contours[*i].points_2d.clone())
+        .collect();
+    let parents: Vec<Option<usize>> = parents(&loops)
+        .into_iter()
+        .map(|p| p.map(|p| closed[p]))
+        .collect();
+    for (k, i) in closed.iter().enumerate() {
+        let mut depth = 0;
+        let mut parent = parents[k];
+        while let Some(p) = parent {
+            depth += 1;
+            parent = parents[closed.iter().position(|i| *i == p).unwrap()];
+        }
+        let contour = &mut section.contours[*i];
+        contour.parent = parents[k];
+        contour.depth = depth;
+        if (signed_area(&contour.points_2d) > 0.0) == contour.is_hole() {
+            contour.points.reverse();
+            contour.points_2d.reverse();
+        }
+    }
+    section
+}
@@ -0,0 +1,334 @@
+use bevy::{
+    app::App,
+    asset::{embedded_asset, Asset},
+    math::{UVec4, Vec4},
+    pbr::{
+        ExtendedMaterial, MaterialExtension, MaterialExtensionKey, MaterialExtensionPipeline,
+        MaterialPlugin, StandardMaterial,
+    },
+    color::{Color, LinearRgba, ColorToComponents},
+    log::warn,
+    reflect::Reflect,
+    render::{
+        mesh::MeshVertexBufferLayoutRef,
+        render_asset::RenderAssets,
+        render_resource::{
+            AsBindGroup, AsBindGroupShaderType, RenderPipelineDescriptor, ShaderRef, ShaderType,
+            SpecializedMeshPipelineError,
+        },
+        texture::GpuImage,
+    },
+};
+use std::{fmt, ops};
+use crate::nplane::{validate_planes, NPlaneCutError};
+use super::*;
+
+/// The maximum number of planes a [`CsgCutExt`] may use.
+pub const CSG_PLANE_MAX: usize = 8;
+/// The maximum number of operations a compiled [`CutExpr`] may have. Each
+/// plane and each operator is one operation.
+pub const CSG_OP_MAX: usize = 32;
+
+pub(crate) fn plugin(app: &mut App) {
+    embedded_asset!(app, "csg_cut.wgsl");
+    app.add_plugins(MaterialPlugin::<CsgCutMaterial>::default());
+}
+
+/// Type alias for boolean expression plane cut material.
+pub type CsgCutMaterial = ExtendedMaterial<StandardMaterial, CsgCutExt>;
+
+/// One operation of a [`CutExpr`] in postfix order.
+#[derive(Reflect, Debug, Clone, Copy, PartialEq, Eq)]
+enum CutOp {
+    Plane(usize),
+    And,
+    Or,
+    Not,
+}
+
+/// A boolean expression of the regions that planes cut.
+///
+/// [`CutExpr::plane`] is the region the plane at that index cuts, _n . position
+/// < w_. The expression is the region that is cut, so `a | b` cuts what
+/// either cuts like [`NPlaneCutExt`](crate::nplane::NPlaneCutExt) and `a & b`
+/// cuts only what both cut.
+///
+/// ```
+/// use bevy_plane_cut::csg::CutExpr;
+/// // A stepped cutaway: (A ∩ B) ∪ C.
+/// let expr = (CutExpr::plane(0) & CutExpr::plane(1)) | CutExpr::plane(2);
+/// assert_eq!(expr.compile().unwrap().len(), 5);
+/// ```
+#[derive(Reflect, Debug, Clone, PartialEq, Eq)]
+pub struct CutExpr {
+    ops: Vec<CutOp>,
+}
+
+impl CutExpr {
+    /// The region that the plane at `index` cuts.
+    pub fn plane(index: usize) -> Self {
+        Self {
+            ops: vec![CutOp::Plane(index)],
+        }
+    }
+
+    /// The region both cut, their intersection.
+    pub fn and(mut self, other: CutExpr) -> Self {
+        self.ops.extend(other.ops);
+        self.ops.push(CutOp::And);
+        self
+    }
+
+    /// The region either cuts, their union.
+    pub fn or(mut self, other: CutExpr) -> Self {
+        self.ops.extend(other.ops);
+        self.ops.push(CutOp::Or);
+        self
+    }
+
+    /// Return one past the largest plane index used, or zero.
+    pub fn plane_count(&self) -> usize {
+        self.ops
+            .iter()
+            .filter_map(|op| match op {
+                CutOp::Plane(i) => Some(*i + 1),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Compile into the operations the shader evaluates, one `u32` each in
+    /// postfix order. The low two bits are the operator: 0 pushes whether the
+    /// plane in the remaining bits cuts, 1 is and, 2 is or, and 3 is not.
+    ///
+    /// Plane indices must be less than [`CSG_PLANE_MAX`].
+    ///
+    /// ```
+    /// use bevy_plane_cut::csg::{CsgCutError, CutExpr, CSG_PLANE_MAX};
+    /// let expr = CutExpr::plane(0) | CutExpr::plane(CSG_PLANE_MAX);
+    /// assert_eq!(
+    ///     expr.compile(),
+    ///     Err(CsgCutError::PlaneIndexTooLarge {
+    ///         index: CSG_PLANE_MAX,
+    ///         max: CSG_PLANE_MAX,
+    ///     })
+    /// );
+    /// ```
+    pub fn compile(&self) -> Result<Vec<u32>, CsgCutError> {
+        if self.ops.len() > CSG_OP_MAX {
+            return Err(CsgCutError::TooManyOps {
+                count: self.ops.len(),
+                max: CSG_OP_MAX,
+            });
+        }
+        self.ops
+            .iter()
+            .map(|op| match op {
+                CutOp::Plane(i) if *i >= CSG_PLANE_MAX => Err(CsgCutError::PlaneIndexTooLarge {
+                    index: *i,
+                    max: CSG_PLANE_MAX,
+                }),
+                CutOp::Plane(i) => Ok((*i as u32) << 2),
+                CutOp::And => Ok(1),
+                CutOp::Or => Ok(2),
+                CutOp::Not => Ok(3),
+            })
+            .collect()
+    }
+}
+
+impl ops::BitAnd for CutExpr {
+    type Output = CutExpr;
+
+    fn bitand(self, other: CutExpr) -> CutExpr {
+        self.and(other)
+    }
+}
+
+impl ops::BitOr for CutExpr {
+    type Output = CutExpr;
+
+    fn bitor(self, other: CutExpr) -> CutExpr {
+        self.or(other)
+    }
+}
+
+impl ops::Not for CutExpr {
+    type Output = CutExpr;
+
+    /// The region this does not cut, its complement.
+    fn not(mut self) -> CutExpr {
+        self.ops.push(CutOp::Not);
+        self
+    }
+}
+
+/// An invalid [`CsgCutExt`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CsgCutError {
+    /// One of the planes is invalid.
+    Plane(NPlaneCutError),
+    /// The expression uses a plane that is not given.
+    MissingPlane {
+        /// The index of the plane.
+        index: usize,
+    },
+    /// The expression uses a plane index of at least the maximum number of
+    /// planes.
+    PlaneIndexTooLarge {
+        /// The index of the plane.
+        index: usize,
+        /// The maximum number of planes.
+        max: usize,
+    },
+    /// The expression has more operations than the maximum.
+    TooManyOps {
+        /// The number of operations.
+        count: usize,
+        /// The maximum number of operations.
+        max: usize,
+    },
+}
+
+impl fmt::Display for CsgCutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CsgCutError::Plane(e) => e.fmt(f),
+            CsgCutError::MissingPlane { index } => {
+                write!(f, "plane {index} is used but not given")
+            }
+            CsgCutError::PlaneIndexTooLarge { index, max } => {
+                write!(f, "plane {index} is used but at most {max} planes are supported")
+            }
+            CsgCutError::TooManyOps { count, max } => {
+                write!(f, "expression has {count} operations but at most {max} are supported")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CsgCutError {}
+
+impl From<NPlaneCutError> for CsgCutError {
+    fn from(e: NPlaneCutError) -> Self {
+        CsgCutError::Plane(e)
+    }
+}
+
+/// The boolean expression plane cut extension.
+///
+/// Cap colors and normals come from the plane whose cap a fragment shows.
+#[derive(Asset, AsBindGroup, Reflect, Debug, Clone)]
+#[uniform(104, CsgCutExtUniform)]
+pub struct CsgCutExt {
+    /// The planes defined like [`PlaneCutExt::plane`] each with the color of
+    /// its cap. Respects a maximum of [`CSG_PLANE_MAX`].
+    pub planes_and_colors: Vec<(Vec4, Color)>,
+    /// The region that is cut.
+    pub expr: CutExpr,
+    /// Define the space the planes are tested in.
+    pub space: Space,
+    /// Is the cut shaded or unlit? Shaded is the default. Note: using the
+    /// deferred renderer will not respect an unlit option.
+    pub shaded: bool,
+}
+
+impl Default for CsgCutExt {
+    fn default() -> Self {
+        Self {
+            planes_and_colors: vec![(Vec4::new(1.0, 0.0, 0.0, 0.0), Color::BLACK)],
+            expr: CutExpr::plane(0),
+            space: Space::default(),
+            shaded: true,
+        }
+    }
+}
+
+impl CsgCutExt {
+    /// Create a world space and shaded cut, or return why it is invalid.
+    pub fn try_new(
+        planes_and_colors: Vec<(Vec4, Color)>,
+        expr: CutExpr,
+    ) -> Result<Self, CsgCutError> {
+        let cut = Self {
+            planes_and_colors,
+            expr,
+            ..Self::default()
+        };
+        cut.validate()?;
+        Ok(cut)
+    }
+
+    /// Check the planes and that the expression compiles with only the planes
+    /// given.
+    pub fn validate(&self) -> Result<(), CsgCutError> {
+        validate_planes(&self.planes_and_colors, Some(CSG_PLANE_MAX))?;
+        let count = self.expr.plane_count();
+        if count > self.planes_and_colors.len() {
+            return Err(CsgCutError::MissingPlane { index: count - 1 });
+        }
+        self.expr.compile().map(|_| ())
+    }
+}
+
+/// The GPU representation of the uniform data of a [`CsgCutExt`].
+#[derive(Clone, Default, ShaderType)]
+struct CsgCutExtUniform {
+    planes: [Vec4; CSG_PLANE_MAX],
+    colors: [Vec4; CSG_PLANE_MAX],
+    ops: [UVec4; CSG_OP_MAX / 4],
+    flags: u32,
+    plane_count: u32,
+    op_count: u32,
+}
+
+impl AsBindGroupShaderType<CsgCutExtUniform> for CsgCutExt {
+    fn as_bind_group_shader_type(&self, _images: &RenderAssets<GpuImage>) -> CsgCutExtUniform {
+        let mut uniform = CsgCutExtUniform {
+            flags: flags(&self.space, self.shaded),
+            ..Default::default()
+        };
+        if let Err(e) = self.validate() {
+            // An empty expression cuts nothing.
+            warn!("CsgCutExt is invalid and will not cut: {e}");
+            return uniform;
+        }
+        for (i, (v, c)) in self.planes_and_colors.iter().enumerate() {
+            uniform.planes[i] = *v;
+            uniform.colors[i] = LinearRgba::from(*c).to_f32_array().into();
+        }
+        let ops = self.expr.compile().unwrap_or_default();
+        for (i, op) in ops.iter().enumerate() {
+            uniform.ops[i / 4][i % 4] = *op;
+        }
+        uniform.plane_count = self.planes_and_colors.len() as u32;
+        uniform.op_count = ops.len() as u32;
+        uniform
+    }
+}
+
+impl MaterialExtension for CsgCutExt {
+    fn fragment_shader() -> ShaderRef {
+        "embedded://bevy_plane_cut/csg_cut.wgsl".into()
+    }
+
+    fn prepass_fragment_shader() -> ShaderRef {
+        "embedded://bevy_plane_cut/csg_cut.wgsl".into()
+    }
+
+    fn deferred_fragment_shader() -> ShaderRef {
+        "embedded://bevy_plane_cut/csg_cut.wgsl".into()
+    }
+
+    fn specialize(
+        pipeline: &MaterialExtensionPipeline,
+        descriptor: &mut RenderPipelineDescriptor,
+        _layout: &MeshVertexBufferLayoutRef,
+        _key: MaterialExtensionKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        descriptor.primitive.cull_mode = None;
+        specialize_prepass(pipeline, descriptor);
+        Ok(())
+    }
+}
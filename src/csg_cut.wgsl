@@ -0,0 +1,247 @@
+#import bevy_pbr::{
+    mesh_view_bindings::view,
+    pbr_fragment::pbr_input_from_standard_material,
+    pbr_functions::alpha_discard,
+    mesh_functions::{get_world_from_local, mesh_normal_local_to_world},
+}
+
+#ifdef PREPASS_PIPELINE
+#import bevy_pbr::{
+    prepass_io::{VertexOutput, FragmentOutput},
+    pbr_deferred_functions::deferred_output,
+    pbr_prepass_functions::{prepass_alpha_discard, calculate_motion_vector},
+}
+#else
+#import bevy_pbr::{
+    forward_io::{VertexOutput, FragmentOutput},
+    pbr_functions::{apply_pbr_lighting, main_pass_post_lighting_processing},
+}
+#endif
+
+struct CsgCutExt {
+    planes: array<vec4<f32>, 8>,
+    colors: array<vec4<f32>, 8>,
+    // Four operations per element, see CutExpr::compile.
+    ops: array<vec4<u32>, 8>,
+    flags: u32,
+    plane_count: u32,
+    op_count: u32,
+}
+const PLANE_CUT_FLAGS_SCREENSPACE_BIT: u32 = 1u;
+const PLANE_CUT_FLAGS_MODELSPACE_BIT: u32 = 2u;
+const PLANE_CUT_FLAGS_SHADED_BIT: u32 = 4u;
+
+const CSG_CUT_OP_PLANE: u32 = 0u;
+const CSG_CUT_OP_AND: u32 = 1u;
+const CSG_CUT_OP_OR: u32 = 2u;
+const CSG_CUT_OP_NOT: u32 = 3u;
+
+@group(2) @binding(104)
+var<uniform> csg_cut_ext: CsgCutExt;
+
+// Convert a plane in the mesh's local space into a plane in world space.
+fn plane_local_to_world(plane: vec4<f32>, instance_index: u32) -> vec4<f32> {
+    let point = plane.xyz * plane.w / dot(plane.xyz, plane.xyz);
+    let world_point = (get_world_from_local(instance_index) * vec4(point, 1.0)).xyz;
+    let world_normal = mesh_normal_local_to_world(plane.xyz, instance_index);
+    return vec4(world_normal, dot(world_normal, world_point));
+}
+
+// Return plane `i` in the space it is tested in.
+fn csg_cut_plane(i: u32, instance_index: u32) -> vec4<f32> {
+    if ((csg_cut_ext.flags & PLANE_CUT_FLAGS_MODELSPACE_BIT) != 0u) {
+        return plane_local_to_world(csg_cut_ext.planes[i], instance_index);
+    }
+    return csg_cut_ext.planes[i];
+}
+
+// Return a bit for each plane that cuts this position.
+fn csg_cut_bits(position: vec3<f32>, instance_index: u32) -> u32 {
+    var bits = 0u;
+    for (var i: u32 = 0u; i < csg_cut_ext.plane_count; i++) {
+        let plane = csg_cut_plane(i, instance_index);
+        if dot(position, plane.xyz) < plane.w {
+            bits |= 1u << i;
+        }
+    }
+    return bits;
+}
+
+// Is a position whose planes cut it per `bits` in the region the expression
+// cuts? The operations are evaluated with a stack of bits whose top is bit 0.
+fn csg_cut_eval(bits: u32) -> bool {
+    var stack = 0u;
+    for (var i: u32 = 0u; i < csg_cut_ext.op_count; i++) {
+        let op = csg_cut_ext.ops[i / 4u][i % 4u];
+        let a = stack & 1u;
+        let b = (stack >> 1u) & 1u;
+        switch (op & 3u) {
+            case CSG_CUT_OP_PLANE: {
+                stack = (stack << 1u) | ((bits >> (op >> 2u)) & 1u);
+            }
+            case CSG_CUT_OP_AND: {
+                stack = ((stack >> 2u) << 1u) | (a & b);
+            }
+            case CSG_CUT_OP_OR: {
+                stack = ((stack >> 2u) << 1u) | (a | b);
+            }
+            default: { // CSG_CUT_OP_NOT
+                stack ^= 1u;
+            }
+        }
+    }
+    return (stack & 1u) != 0u;
+}
+
+// Discard the fragment if it is cut. Otherwise return the index of the plane
+// whose cap a back face shows or -1.
+//
+// The cap is where the view ray last leaves the cut region before reaching
+// this fragment. In screen space the ray runs along the pixel's depth.
+fn csg_cut(
+    frag_coord: vec4<f32>,
+    world_position: vec4<f32>,
+    instance_index: u32,
+    is_front: bool,
+) -> i32 {
+    var position: vec3<f32>;
+    var origin: vec3<f32>;
+    var ray: vec3<f32>;
+    var t_max: f32;
+    if ((csg_cut_ext.flags & PLANE_CUT_FLAGS_SCREENSPACE_BIT) != 0u) {
+        // Screenspace, where the near plane has a depth of one.
+        position = frag_coord.xyz;
+        origin = vec3(frag_coord.xy, 1.0);
+        ray = vec3(0.0, 0.0, -1.0);
+        t_max = 1.0 - frag_coord.z;
+    } else {
+        // World or object space
+        position = world_position.xyz;
+        origin = view.world_position;
+        t_max = length(position - origin);
+        ray = (position - origin) / t_max;
+    }
+    if csg_cut_eval(csg_cut_bits(position, instance_index)) {
+        discard;
+    }
+    if is_front {
+        return -1;
+    }
+
+    var plane_index: i32 = -1;
+    var t_cap = 0.0;
+    for (var i: u32 = 0u; i < csg_cut_ext.plane_count; i++) {
+        let plane = csg_cut_plane(i, instance_index);
+        let denom = dot(plane.xyz, ray);
+        if (abs(denom) < 1e-6) {
+            // The ray is parallel to the plane.
+            continue;
+        }
+        let t = (plane.w - dot(plane.xyz, origin)) / denom;
+        if t <= t_cap || t >= t_max {
+            continue;
+        }
+        // Does the ray leave the cut region as it crosses this plane? The
+        // crossing itself decides this plane's bit on either side of it.
+        let bit = 1u << i;
+        let bits = csg_cut_bits(origin + t * ray, instance_index) & ~bit;
+        let toward_kept = denom > 0.0;
+        let before = select(bits, bits | bit, toward_kept);
+        let after = select(bits | bit, bits, toward_kept);
+        if csg_cut_eval(before) && !csg_cut_eval(after) {
+            plane_index = i32(i);
+            t_cap = t;
+        }
+    }
+    return plane_index;
+}
+
+// Return the cap normal of plane `i` facing the viewer.
+fn csg_cut_cap_normal(i: u32, world_position: vec4<f32>, instance_index: u32) -> vec3<f32> {
+    let n = normalize(csg_cut_plane(i, instance_index).xyz);
+    return faceForward(n, world_position.xyz - view.world_position, n);
+}
+
+// PLANE_CUT_PREPASS is set by the plugin for the depth, normal, and motion
+// vector prepass, which also draws shadows.
+#ifdef PLANE_CUT_PREPASS
+#ifdef PREPASS_FRAGMENT
+@fragment
+fn fragment(
+    in: VertexOutput,
+    @builtin(front_facing) is_front: bool,
+) -> FragmentOutput {
+    let plane_index = csg_cut(in.position, in.world_position, in.instance_index, is_front);
+    prepass_alpha_discard(in);
+
+    var out: FragmentOutput;
+#ifdef UNCLIPPED_DEPTH_ORTHO_EMULATION
+    out.frag_depth = in.unclipped_depth;
+#endif
+#ifdef NORMAL_PREPASS
+    var normal = in.world_normal;
+    if (!is_front && plane_index >= 0) {
+        normal = csg_cut_cap_normal(u32(plane_index), in.world_position, in.instance_index);
+    }
+    out.normal = vec4(normal * 0.5 + vec3(0.5), 1.0);
+#endif
+#ifdef MOTION_VECTOR_PREPASS
+    out.motion_vector = calculate_motion_vector(in.world_position, in.previous_world_position);
+#endif
+    return out;
+}
+#else
+// Depth only, e.g. shadows. The plane cut plugin adds this fragment stage
+// because without it the cut away portion would still write depth.
+@fragment
+fn fragment(
+    in: VertexOutput,
+    @builtin(front_facing) is_front: bool,
+) {
+    csg_cut(in.position, in.world_position, in.instance_index, is_front);
+    prepass_alpha_discard(in);
+}
+#endif
+#else
+@fragment
+fn fragment(
+    in_: VertexOutput,
+    @builtin(front_facing) is_front: bool,
+) -> FragmentOutput {
+    var in = in_;
+
+    let shaded = (csg_cut_ext.flags & PLANE_CUT_FLAGS_SHADED_BIT) != 0u;
+    let plane_index = csg_cut(in.position, in.world_position, in.instance_index, is_front);
+    let is_cap = !is_front && plane_index >= 0;
+
+    if (is_cap && shaded) {
+        in.world_normal = csg_cut_cap_normal(u32(plane_index), in.world_position, in.instance_index);
+    }
+    // Generate a PbrInput struct from the StandardMaterial bindings
+    var pbr_input = pbr_input_from_standard_material(in, is_front);
+
+    if (is_cap && shaded) {
+        pbr_input.material.base_color = csg_cut_ext.colors[plane_index];
+    }
+
+    // Alpha discard
+    pbr_input.material.base_color = alpha_discard(pbr_input.material, pbr_input.material.base_color);
+
+#ifdef PREPASS_PIPELINE
+    // In deferred mode we can't modify anything after that, as lighting is run
+    // in a separate fullscreen shader.
+    let out = deferred_output(in, pbr_input);
+#else
+    var out: FragmentOutput;
+    // Apply lighting
+    out.color = apply_pbr_lighting(pbr_input);
+
+    out.color = main_pass_post_lighting_processing(pbr_input, out.color);
+    if (!shaded && is_cap) {
+        out.color = csg_cut_ext.colors[plane_index];
+    }
+#endif
+
+    return out;
+}
+#endif
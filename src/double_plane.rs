@@ -0,0 +1,119 @@
+use bevy::{
+    app::App,
+    asset::{embedded_asset, Asset},
+    math::Vec4,
+    pbr::{
+        ExtendedMaterial, MaterialExtension, MaterialExtensionKey, MaterialExtensionPipeline,
+        MaterialPlugin, StandardMaterial,
+    },
+    color::{Color, LinearRgba, ColorToComponents},
+    reflect::Reflect,
+    render::{
+        mesh::MeshVertexBufferLayoutRef,
+        render_asset::RenderAssets,
+        render_resource::{
+            AsBindGroup, AsBindGroupShaderType, RenderPipelineDescriptor, ShaderRef, ShaderType,
+            SpecializedMeshPipelineError,
+        },
+        texture::GpuImage,
+    },
+};
+use super::*;
+
+pub(crate) fn plugin(app: &mut App) {
+    embedded_asset!(app, "double_plane_cut.wgsl");
+    app.add_plugins(MaterialPlugin::<DoublePlaneCutMaterial>::default());
+}
+
+/// Type alias for double plane cut material.
+pub type DoublePlaneCutMaterial = ExtendedMaterial<StandardMaterial, DoublePlaneCutExt>;
+
+/// The double plane cut extension.
+///
+/// Each plane is defined like [`PlaneCutExt::plane`]. The portion that either
+/// plane cuts is removed. Unlike [`NPlaneCutExt`](crate::nplane::NPlaneCutExt)
+/// each plane has its own space and shading.
+#[derive(Asset, AsBindGroup, Reflect, Debug, Clone)]
+#[uniform(102, DoublePlaneCutExtUniform)]
+pub struct DoublePlaneCutExt {
+    /// The first plane _(nx, ny, nz, w)_.
+    pub plane1: Vec4,
+    /// Define the color of the first cut.
+    pub color1: Color,
+    /// Define the space the first plane is tested in.
+    pub space1: Space,
+    /// Is the first cut shaded or unlit?
+    pub shaded1: bool,
+    /// The second plane _(nx, ny, nz, w)_.
+    pub plane2: Vec4,
+    /// Define the color of the second cut.
+    pub color2: Color,
+    /// Define the space the second plane is tested in.
+    pub space2: Space,
+    /// Is the second cut shaded or unlit?
+    pub shaded2: bool,
+}
+
+impl Default for DoublePlaneCutExt {
+    fn default() -> Self {
+        Self {
+            plane1: Vec4::new(1.0, 0.0, 0.0, 0.0),
+            color1: Color::BLACK,
+            space1: Space::default(),
+            shaded1: true,
+            plane2: Vec4::new(0.0, 1.0, 0.0, 0.0),
+            color2: Color::BLACK,
+            space2: Space::default(),
+            shaded2: true,
+        }
+    }
+}
+
+/// The GPU representation of the uniform data of a [`DoublePlaneCutExt`].
+#[derive(Clone, Default, ShaderType)]
+struct DoublePlaneCutExtUniform {
+    plane1: Vec4,
+    color1: Vec4,
+    flags1: u32,
+    plane2: Vec4,
+    color2: Vec4,
+    flags2: u32,
+}
+
+impl AsBindGroupShaderType<DoublePlaneCutExtUniform> for DoublePlaneCutExt {
+    fn as_bind_group_shader_type(&self, _images: &RenderAssets<GpuImage>) -> DoublePlaneCutExtUniform {
+        DoublePlaneCutExtUniform {
+            plane1: self.plane1,
+            color1: LinearRgba::from(self.color1).to_f32_array().into(),
+            flags1: flags(&self.space1, self.shaded1),
+            plane2: self.plane2,
+            color2: LinearRgba::from(self.color2).to_f32_array().into(),
+            flags2: flags(&self.space2, self.shaded2),
+        }
+    }
+}
+
+impl MaterialExtension for DoublePlaneCutExt {
+    fn fragment_shader() -> ShaderRef {
+        "embedded://bevy_plane_cut/double_plane_cut.wgsl".into()
+    }
+
+    fn prepass_fragment_shader() -> ShaderRef {
+        "embedded://bevy_plane_cut/double_plane_cut.wgsl".into()
+    }
+
+    fn deferred_fragment_shader() -> ShaderRef {
+        "embedded://bevy_plane_cut/double_plane_cut.wgsl".into()
+    }
+
+    fn specialize(
+        pipeline: &MaterialExtensionPipeline,
+        descriptor: &mut RenderPipelineDescriptor,
+        _layout: &MeshVertexBufferLayoutRef,
+        _key: MaterialExtensionKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        descriptor.primitive.cull_mode = None;
+        specialize_prepass(pipeline, descriptor);
+        Ok(())
+    }
+}
@@ -1,12 +1,15 @@
 #import bevy_pbr::{
     pbr_fragment::pbr_input_from_standard_material,
     pbr_functions::alpha_discard,
+    mesh_functions::{get_world_from_local, mesh_normal_local_to_world},
+    view_transformations::{frag_coord_to_ndc, position_ndc_to_world},
 }
 
 #ifdef PREPASS_PIPELINE
 #import bevy_pbr::{
     prepass_io::{VertexOutput, FragmentOutput},
     pbr_deferred_functions::deferred_output,
+    pbr_prepass_functions::{prepass_alpha_discard, calculate_motion_vector},
 }
 #else
 #import bevy_pbr::{
@@ -25,11 +28,137 @@ struct DoublePlaneCutExt {
 }
 
 const PLANE_CUT_FLAGS_SCREENSPACE_BIT: u32 = 1u;
+const PLANE_CUT_FLAGS_MODELSPACE_BIT: u32 = 2u;
 const PLANE_CUT_FLAGS_SHADED_BIT: u32 = 4u;
 
-@group(2) @binding(100)
+@group(2) @binding(102)
 var<uniform> double_plane_cut_ext: DoublePlaneCutExt;
 
+// Convert a plane in the mesh's local space into a plane in world space.
+fn plane_local_to_world(plane: vec4<f32>, instance_index: u32) -> vec4<f32> {
+    let point = plane.xyz * plane.w / dot(plane.xyz, plane.xyz);
+    let world_point = (get_world_from_local(instance_index) * vec4(point, 1.0)).xyz;
+    let world_normal = mesh_normal_local_to_world(plane.xyz, instance_index);
+    return vec4(world_normal, dot(world_normal, world_point));
+}
+
+// Return the plane in the space it is tested in.
+fn plane_cut_plane(plane: vec4<f32>, flags: u32, instance_index: u32) -> vec4<f32> {
+    if ((flags & PLANE_CUT_FLAGS_MODELSPACE_BIT) != 0u) {
+        return plane_local_to_world(plane, instance_index);
+    }
+    return plane;
+}
+
+// Is this fragment on the cut side of the plane?
+fn plane_cut_is_cut(frag_coord: vec4<f32>, world_position: vec4<f32>, plane: vec4<f32>, flags: u32) -> bool {
+    if ((flags & PLANE_CUT_FLAGS_SCREENSPACE_BIT) != 0u) {
+        // Screenspace
+        return dot(frag_coord.xyz, plane.xyz) < plane.w;
+    } else {
+        // World or object space
+        return dot(world_position.xyz, plane.xyz) < plane.w;
+    }
+}
+
+// Return how far a fragment is from a plane, in pixels in screen space and in
+// world units otherwise.
+fn plane_cut_distance(frag_coord: vec4<f32>, world_position: vec4<f32>, plane: vec4<f32>, flags: u32) -> f32 {
+    var position = world_position.xyz;
+    if ((flags & PLANE_CUT_FLAGS_SCREENSPACE_BIT) != 0u) {
+        position = frag_coord.xyz;
+    }
+    return abs(dot(position, plane.xyz) - plane.w) / length(plane.xyz);
+}
+
+// Return where a screen space plane is at a pixel in world space.
+fn screen_plane_world_point(plane: vec4<f32>, xy: vec2<f32>) -> vec3<f32> {
+    let depth = (plane.w - dot(plane.xy, xy)) / plane.z;
+    return position_ndc_to_world(frag_coord_to_ndc(vec4(xy, depth, 1.0)));
+}
+
+// Return the world normal of a plane's cap at a frag coord. A screen space
+// plane is in frag coords, so its normal is found from where it is at the
+// neighboring pixels and turned toward the camera.
+fn plane_cut_cap_normal(frag_coord: vec4<f32>, plane: vec4<f32>, flags: u32) -> vec3<f32> {
+    if ((flags & PLANE_CUT_FLAGS_SCREENSPACE_BIT) == 0u) {
+        return -normalize(plane.xyz);
+    }
+    let xy = frag_coord.xy;
+    let toward_camera = position_ndc_to_world(frag_coord_to_ndc(vec4(xy, 1.0, 1.0)))
+        - position_ndc_to_world(frag_coord_to_ndc(vec4(xy, 0.5, 1.0)));
+    if (abs(plane.z) < 1e-6) {
+        // The plane is edge on, so its cap is too.
+        return normalize(toward_camera);
+    }
+    let point = screen_plane_world_point(plane, xy);
+    let normal = normalize(cross(
+        screen_plane_world_point(plane, xy + vec2(1.0, 0.0)) - point,
+        screen_plane_world_point(plane, xy + vec2(0.0, 1.0)) - point,
+    ));
+    return select(-normal, normal, dot(normal, toward_camera) >= 0.0);
+}
+
+// Return which plane's cap a back face shows, the nearest one.
+fn plane_cut_cap_index(frag_coord: vec4<f32>, world_position: vec4<f32>, plane1: vec4<f32>, plane2: vec4<f32>) -> u32 {
+    let distance1 = plane_cut_distance(frag_coord, world_position, plane1, double_plane_cut_ext.flags1);
+    let distance2 = plane_cut_distance(frag_coord, world_position, plane2, double_plane_cut_ext.flags2);
+    return select(2u, 1u, distance1 < distance2);
+}
+
+// PLANE_CUT_PREPASS is set by the plugin for the depth, normal, and motion
+// vector prepass, which also draws shadows.
+#ifdef PLANE_CUT_PREPASS
+#ifdef PREPASS_FRAGMENT
+@fragment
+fn fragment(
+    in: VertexOutput,
+    @builtin(front_facing) is_front: bool,
+) -> FragmentOutput {
+    let plane1 = plane_cut_plane(double_plane_cut_ext.plane1, double_plane_cut_ext.flags1, in.instance_index);
+    let plane2 = plane_cut_plane(double_plane_cut_ext.plane2, double_plane_cut_ext.flags2, in.instance_index);
+    if (plane_cut_is_cut(in.position, in.world_position, plane1, double_plane_cut_ext.flags1)
+        || plane_cut_is_cut(in.position, in.world_position, plane2, double_plane_cut_ext.flags2)) {
+        discard;
+    }
+    prepass_alpha_discard(in);
+
+    var out: FragmentOutput;
+#ifdef UNCLIPPED_DEPTH_ORTHO_EMULATION
+    out.frag_depth = in.unclipped_depth;
+#endif
+#ifdef NORMAL_PREPASS
+    var normal = in.world_normal;
+    if (!is_front) {
+        // Use the normal of the closest plane
+        if (plane_cut_cap_index(in.position, in.world_position, plane1, plane2) == 1u) {
+            normal = plane_cut_cap_normal(in.position, plane1, double_plane_cut_ext.flags1);
+        } else {
+            normal = plane_cut_cap_normal(in.position, plane2, double_plane_cut_ext.flags2);
+        }
+    }
+    out.normal = vec4(normal * 0.5 + vec3(0.5), 1.0);
+#endif
+#ifdef MOTION_VECTOR_PREPASS
+    out.motion_vector = calculate_motion_vector(in.world_position, in.previous_world_position);
+#endif
+    return out;
+}
+#else
+// Depth only, e.g. shadows. The plane cut plugin adds this fragment stage
+// because without it the cut away portion would still write depth.
+@fragment
+fn fragment(in: VertexOutput) {
+    let plane1 = plane_cut_plane(double_plane_cut_ext.plane1, double_plane_cut_ext.flags1, in.instance_index);
+    let plane2 = plane_cut_plane(double_plane_cut_ext.plane2, double_plane_cut_ext.flags2, in.instance_index);
+    if (plane_cut_is_cut(in.position, in.world_position, plane1, double_plane_cut_ext.flags1)
+        || plane_cut_is_cut(in.position, in.world_position, plane2, double_plane_cut_ext.flags2)) {
+        discard;
+    }
+    prepass_alpha_discard(in);
+}
+#endif
+#else
 @fragment
 fn fragment(
     in_: VertexOutput,
@@ -39,69 +168,33 @@ fn fragment(
 
     let shaded1 = (double_plane_cut_ext.flags1 & PLANE_CUT_FLAGS_SHADED_BIT) != 0u;
     let shaded2 = (double_plane_cut_ext.flags2 & PLANE_CUT_FLAGS_SHADED_BIT) != 0u;
+    let plane1 = plane_cut_plane(double_plane_cut_ext.plane1, double_plane_cut_ext.flags1, in.instance_index);
+    let plane2 = plane_cut_plane(double_plane_cut_ext.plane2, double_plane_cut_ext.flags2, in.instance_index);
     
     // Check which plane cut we're on (if any)
-    var cut_by_plane1 = false;
-    var cut_by_plane2 = false;
-    
-    if ((double_plane_cut_ext.flags1 & PLANE_CUT_FLAGS_SCREENSPACE_BIT) != 0u) {
-        // Screenspace for plane 1
-        if (dot(in.position.xyz, double_plane_cut_ext.plane1.xyz) < double_plane_cut_ext.plane1.w) {
-            cut_by_plane1 = true;
-        }
-    } else {
-        // World space for plane 1
-        if (dot(in.world_position.xyz, double_plane_cut_ext.plane1.xyz) < double_plane_cut_ext.plane1.w) {
-            cut_by_plane1 = true;
-        }
-    }
-    
-    if ((double_plane_cut_ext.flags2 & PLANE_CUT_FLAGS_SCREENSPACE_BIT) != 0u) {
-        // Screenspace for plane 2
-        if (dot(in.position.xyz, double_plane_cut_ext.plane2.xyz) < double_plane_cut_ext.plane2.w) {
-            cut_by_plane2 = true;
-        }
-    } else {
-        // World space for plane 2
-        if (dot(in.world_position.xyz, double_plane_cut_ext.plane2.xyz) < double_plane_cut_ext.plane2.w) {
-            cut_by_plane2 = true;
-        }
-    }
+    let cut_by_plane1 = plane_cut_is_cut(in.position, in.world_position, plane1, double_plane_cut_ext.flags1);
+    let cut_by_plane2 = plane_cut_is_cut(in.position, in.world_position, plane2, double_plane_cut_ext.flags2);
     
     // Discard if cut by either plane
     if (cut_by_plane1 || cut_by_plane2) {
         discard;
     }
 
-    // Determine which cut surface we're on
+    // Determine which cut surface we're on: a back face shows the cap of the
+    // closest plane, lit or not.
     var use_plane1_color = false;
     var use_plane2_color = false;
     
     if (!is_front) {
-        if (shaded1) {
-            // Check if we're on the cut surface of plane 1
-            let distance1 = abs(dot(in.world_position.xyz, double_plane_cut_ext.plane1.xyz) - double_plane_cut_ext.plane1.w);
-            let distance2 = abs(dot(in.world_position.xyz, double_plane_cut_ext.plane2.xyz) - double_plane_cut_ext.plane2.w);
-            
-            // Use the color of the closest plane
-            if (distance1 < distance2) {
-                in.world_normal = -double_plane_cut_ext.plane1.xyz;
-                use_plane1_color = true;
-            } else {
-                in.world_normal = -double_plane_cut_ext.plane2.xyz;
-                use_plane2_color = true;
+        if (plane_cut_cap_index(in.position, in.world_position, plane1, plane2) == 1u) {
+            use_plane1_color = true;
+            if (shaded1) {
+                in.world_normal = plane_cut_cap_normal(in.position, plane1, double_plane_cut_ext.flags1);
             }
-        } else if (shaded2) {
-            // Similar logic but prefer plane 2
-            let distance1 = abs(dot(in.world_position.xyz, double_plane_cut_ext.plane1.xyz) - double_plane_cut_ext.plane1.w);
-            let distance2 = abs(dot(in.world_position.xyz, double_plane_cut_ext.plane2.xyz) - double_plane_cut_ext.plane2.w);
-            
-            if (distance2 <= distance1) {
-                in.world_normal = -double_plane_cut_ext.plane2.xyz;
-                use_plane2_color = true;
-            } else {
-                in.world_normal = -double_plane_cut_ext.plane1.xyz;
-                use_plane1_color = true;
+        } else {
+            use_plane2_color = true;
+            if (shaded2) {
+                in.world_normal = plane_cut_cap_normal(in.position, plane2, double_plane_cut_ext.flags2);
             }
         }
     }
@@ -142,3 +235,4 @@ fn fragment(
 
     return out;
 }
+#endif
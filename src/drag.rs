@@ -0,0 +1,540 @@
+use bevy::{
+    app::{App, Plugin, PostUpdate, PreUpdate},
+    asset::Assets,
+    color::{palettes::basic, Color},
+    ecs::{
+        component::Component,
+        entity::Entity,
+        event::{Event, EventWriter},
+        observer::Trigger,
+        reflect::ReflectComponent,
+        schedule::IntoScheduleConfigs,
+        system::{Query, Res, ResMut},
+    },
+    gizmos::{
+        config::{GizmoConfigGroup, GizmoConfigStore},
+        gizmos::Gizmos,
+        AppGizmoBuilder,
+    },
+    log::warn_once,
+    math::{Isometry3d, Quat, Ray3d, Vec3, Vec4, Vec4Swizzles},
+    picking::{
+        backend::{
+            ray::{RayId, RayMap},
+            HitData, PointerHits,
+        },
+        events::{Drag, DragEnd, DragStart, Pointer},
+        pointer::PointerButton,
+        PickSet,
+    },
+    reflect::Reflect,
+    render::camera::Camera,
+    transform::{
+        components::{GlobalTransform, Transform},
+        TransformSystem,
+    },
+};
+use super::*;
+use crate::{
+    follow::{PlaneCut, PlaneCutSystem, PlaneCutTarget},
+    nplane::NPlaneCutMaterial,
+};
+
+/// Drag a plane cut with the mouse by its [`PlaneCutHandle`]s.
+///
+/// The handles are drawn with gizmos and picked with Bevy's picking, so its
+/// plugins must be added too, which `DefaultPlugins` does. A
+/// [`PlaneCutDragged`] event is sent when a drag finishes.
+pub struct PlaneCutDragPlugin;
+
+impl Plugin for PlaneCutDragPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<PlaneCutHandle>()
+            .add_event::<PlaneCutDragged>()
+            .init_gizmo_group::<PlaneCutHandleGizmos>()
+            .add_systems(PreUpdate, pick_handles.in_set(PickSet::Backend))
+            .add_systems(
+                PostUpdate,
+                draw_handles
+                    .after(TransformSystem::TransformPropagate)
+                    .after(PlaneCutSystem::UpdatePlanes),
+            )
+            .add_observer(start_drag)
+            .add_observer(drag)
+            .add_observer(end_drag);
+        // Draw the handles over what they cut.
+        let (config, _) = app
+            .world_mut()
+            .resource_mut::<GizmoConfigStore>()
+            .into_inner()
+            .config_mut::<PlaneCutHandleGizmos>();
+        config.depth_bias = -1.0;
+    }
+}
+
+/// The gizmos of the [`PlaneCutDragPlugin`]'s handles.
+#[derive(Default, Reflect, GizmoConfigGroup, Debug, Clone)]
+pub struct PlaneCutHandleGizmos;
+
+/// Handles to drag a plane cut: an arrow that moves the plane along its normal
+/// and two rings that tilt it about the plane's axes.
+///
+/// If the entity has a [`PlaneCut`], the handles move its `Transform`, which
+/// ought not to have a parent. Otherwise they edit the plane of `target`. The
+/// handles sit on the plane nearest the entity.
+#[derive(Component, Reflect, Debug, Clone)]
+#[reflect(Component)]
+pub struct PlaneCutHandle {
+    /// The plane to edit if the entity has no [`PlaneCut`].
+    ///
+    /// A [`Space::World`] plane is edited as is. A [`Space::Model`] plane is
+    /// taken to be in the model space of this entity's `GlobalTransform`, so
+    /// put the handles on the cut mesh, and is converted to world space and
+    /// back. [`Space::Screen`] planes have no place in the world, so their
+    /// handles are not shown.
+    pub target: Option<PlaneCutTarget>,
+    /// The length of the arrow and the radius of the rings in world units.
+    /// One is the default.
+    pub size: f32,
+    /// Snap moves to multiples of this distance. Zero, the default, does not
+    /// snap.
+    pub snap_distance: f32,
+    /// Snap tilts to multiples of this angle in radians. Zero, the default,
+    /// does not snap.
+    pub snap_angle: f32,
+    #[reflect(ignore)]
+    drag: Option<DragState>,
+}
+
+impl Default for PlaneCutHandle {
+    fn default() -> Self {
+        Self {
+            target: None,
+            size: 1.0,
+            snap_distance: 0.0,
+            snap_angle: 0.0,
+            drag: None,
+        }
+    }
+}
+
+impl PlaneCutHandle {
+    /// Create handles that edit a material's plane.
+    pub fn new(target: impl Into<PlaneCutTarget>) -> Self {
+        Self {
+            target: Some(target.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Set the size of the handles.
+    pub fn with_size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Set the snapping increments of moves and tilts.
+    pub fn with_snap(mut self, distance: f32, angle: f32) -> Self {
+        self.snap_distance = distance;
+        self.snap_angle = angle;
+        self
+    }
+}
+
+/// Sent when a drag of a [`PlaneCutHandle`] finishes.
+#[derive(Event, Debug, Clone)]
+pub struct PlaneCutDragged {
+    /// The entity with the handles.
+    pub entity: Entity,
+    /// The world space plane where the drag left it.
+    pub plane: Vec4,
+}
+
+/// A part of the handles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Part {
+    /// The arrow along the normal.
+    Arrow,
+    /// The ring about the plane's x or y axis.
+    Ring(usize),
+}
+
+/// Where the handles of a plane are.
+#[derive(Debug, Clone, Copy)]
+struct Frame {
+    center: Vec3,
+    normal: Vec3,
+    /// The same axes as `contour::CrossSection`'s.
+    axes: [Vec3; 2],
+}
+
+impl Frame {
+    fn new(plane: Vec4, near: Vec3) -> Option<Self> {
+        let length = plane.xyz().length();
+        if length == 0.0 || !length.is_finite() {
+            return None;
+        }
+        let normal = plane.xyz() / length;
+        let x_axis = normal.any_orthonormal_vector();
+        Some(Self {
+            center: near - normal * (normal.dot(near) - plane.w / length),
+            normal,
+            axes: [x_axis, x_axis.cross(normal)],
+        })
+    }
+
+    /// Return the nearest part a ray hits and how far along the ray.
+    fn hit(&self, ray: Ray3d, size: f32) -> Option<(Part, f32)> {
+        let tolerance = size * 0.08;
+        let mut nearest: Option<(Part, f32)> = None;
+        let mut consider = |part, t: f32| {
+            if t > 0.0 && nearest.is_none_or(|(_, nearest_t)| t < nearest_t) {
+                nearest = Some((part, t));
+            }
+        };
+        if let Some((t, s)) = closest_points(ray, self.center, self.normal) {
+            let on_arrow = self.center + self.normal * s.clamp(0.0, size);
+            if ray.get_point(t).distance(on_arrow) < tolerance {
+                consider(Part::Arrow, t);
+            }
+        }
+        for (i, axis) in self.axes.into_iter().enumerate() {
+            if let Some(t) = ray_plane(ray, self.center, axis) {
+                if (ray.get_point(t).distance(self.center) - size).abs() < tolerance {
+                    consider(Part::Ring(i), t);
+                }
+            }
+        }
+        nearest
+    }
+
+    /// Return where a ray points along a part: the distance along the normal
+    /// for the arrow and the angle about its axis for a ring.
+    fn coordinate(&self, ray: Ray3d, part: Part) -> Option<f32> {
+        match part {
+            Part::Arrow => closest_points(ray, self.center, self.normal).map(|(_, s)| s),
+            Part::Ring(i) => {
+                let axis = self.axes[i];
+                let v = ray.get_point(ray_plane(ray, self.center, axis)?) - self.center;
+                Some(v.dot(axis.cross(self.normal)).atan2(v.dot(self.normal)))
+            }
+        }
+    }
+
+    /// Return the motion of dragging a part by a change in its coordinate.
+    fn motion(&self, part: Part, delta: f32) -> Transform {
+        match part {
+            Part::Arrow => Transform::from_translation(self.normal * delta),
+            Part::Ring(i) => {
+                let rotation = Quat::from_axis_angle(self.axes[i], delta);
+                let mut motion = Transform::IDENTITY;
+                motion.rotate_around(self.center, rotation);
+                motion
+            }
+        }
+    }
+}
+
+/// Return how far along a ray and a line their closest points are.
+fn closest_points(ray: Ray3d, point: Vec3, direction: Vec3) -> Option<(f32, f32)> {
+    let w = ray.origin - point;
+    let b = ray.direction.dot(direction);
+    let d = ray.direction.dot(w);
+    let e = direction.dot(w);
+    let denominator = 1.0 - b * b;
+    if denominator < 1e-6 {
+        return None;
+    }
+    Some(((b * e - d) / denominator, (e - b * d) / denominator))
+}
+
+/// Return how far along a ray it meets a plane through a point.
+fn ray_plane(ray: Ray3d, point: Vec3, normal: Vec3) -> Option<f32> {
+    let denominator = ray.direction.dot(normal);
+    if denominator.abs() < 1e-6 {
+        return None;
+    }
+    Some((point - ray.origin).dot(normal) / denominator)
+}
+
+/// Snap a value to a multiple of a step unless it is zero.
+fn snap(value: f32, step: f32) -> f32 {
+    if step > 0.0 {
+        (value / step).round() * step
+    } else {
+        value
+    }
+}
+
+/// What a drag started with.
+#[derive(Debug, Clone)]
+struct DragState {
+    part: Part,
+    camera: Entity,
+    frame: Frame,
+    plane: Vec4,
+    transform: Option<Transform>,
+    /// The transform of a model space target.
+    model: Option<GlobalTransform>,
+    start: f32,
+}
+
+/// What the handles of an entity are drawn and picked from.
+type HandleData = (
+    &'static PlaneCutHandle,
+    Option<&'static PlaneCut>,
+    Option<&'static Transform>,
+    Option<&'static GlobalTransform>,
+);
+
+type HandleDataMut = (
+    &'static mut PlaneCutHandle,
+    Option<&'static PlaneCut>,
+    Option<&'static Transform>,
+    Option<&'static GlobalTransform>,
+);
+
+/// Return the world space plane and the handles' frame of an entity and the
+/// transform of its target if that is in model space.
+fn handle_frame(
+    handle: &PlaneCutHandle,
+    cut: Option<&PlaneCut>,
+    transform: Option<&Transform>,
+    global_transform: Option<&GlobalTransform>,
+    materials: &Assets<PlaneCutMaterial>,
+    nplane_materials: &Assets<NPlaneCutMaterial>,
+) -> Option<(Vec4, Frame, Option<GlobalTransform>)> {
+    let (plane, near, model) = match (cut, transform) {
+        (Some(cut), Some(transform)) => (
+            cut.plane(&GlobalTransform::from(*transform)),
+            transform.translation,
+            None,
+        ),
+        _ => {
+            let (plane, space) = match handle.target.as_ref()? {
+                PlaneCutTarget::Single(handle) => {
+                    let extension = &materials.get(handle)?.extension;
+                    (extension.plane, &extension.space)
+                }
+                PlaneCutTarget::NPlane(handle, index) => {
+                    let extension = &nplane_materials.get(handle)?.extension;
+                    (extension.planes_and_colors.get(*index)?.0, &extension.space)
+                }
+            };
+            let near = global_transform.map_or(Vec3::ZERO, GlobalTransform::translation);
+            match space {
+                Space::World => (plane, near, None),
+                Space::Model => {
+                    let model = *global_transform?;
+                    (plane_local_to_world(plane, &model), near, Some(model))
+                }
+                Space::Screen => {
+                    warn_once!("PlaneCutHandle cannot edit a screen space plane.");
+                    return None;
+                }
+            }
+        }
+    };
+    Some((plane, Frame::new(plane, near)?, model))
+}
+
+/// Report the handles under each pointer to Bevy's picking.
+fn pick_handles(
+    ray_map: Res<RayMap>,
+    handles: Query<(Entity, HandleData)>,
+    cameras: Query<&Camera>,
+    materials: Res<Assets<PlaneCutMaterial>>,
+    nplane_materials: Res<Assets<NPlaneCutMaterial>>,
+    mut output: EventWriter<PointerHits>,
+) {
+    for (&ray_id, &ray) in ray_map.iter() {
+        let Ok(camera) = cameras.get(ray_id.camera) else {
+            continue;
+        };
+        let picks: Vec<(Entity, HitData)> = handles
+            .iter()
+            .filter_map(|(entity, (handle, cut, transform, global_transform))| {
+                let (_, frame, _) = handle_frame(
+                    handle,
+                    cut,
+                    transform,
+                    global_transform,
+                    &materials,
+                    &nplane_materials,
+                )?;
+                let (_, t) = frame.hit(ray, handle.size)?;
+                Some((
+                    entity,
+                    HitData::new(ray_id.camera, t, Some(ray.get_point(t)), None),
+                ))
+            })
+            .collect();
+        if !picks.is_empty() {
+            // Like UI, the handles are over the meshes of their camera.
+            output.write(PointerHits::new(ray_id.pointer, picks, camera.order as f32 + 0.5));
+        }
+    }
+}
+
+fn start_drag(
+    trigger: Trigger<Pointer<DragStart>>,
+    mut handles: Query<HandleDataMut>,
+    ray_map: Res<RayMap>,
+    materials: Res<Assets<PlaneCutMaterial>>,
+    nplane_materials: Res<Assets<NPlaneCutMaterial>>,
+) {
+    let event = trigger.event();
+    if event.button != PointerButton::Primary {
+        return;
+    }
+    let Ok((mut handle, cut, transform, global_transform)) = handles.get_mut(event.target) else {
+        return;
+    };
+    let camera = event.hit.camera;
+    let Some(&ray) = ray_map.map.get(&RayId::new(camera, event.pointer_id)) else {
+        return;
+    };
+    let Some((plane, frame, model)) = handle_frame(
+        &handle,
+        cut,
+        transform,
+        global_transform,
+        &materials,
+        &nplane_materials,
+    ) else {
+        return;
+    };
+    let Some((part, _)) = frame.hit(ray, handle.size) else {
+        return;
+    };
+    let Some(start) = frame.coordinate(ray, part) else {
+        return;
+    };
+    handle.drag = Some(DragState {
+        part,
+        camera,
+        frame,
+        plane,
+        transform: cut.and(transform.copied()),
+        model,
+        start,
+    });
+}
+
+fn drag(
+    trigger: Trigger<Pointer<Drag>>,
+    mut handles: Query<(&PlaneCutHandle, Option<&mut Transform>)>,
+    ray_map: Res<RayMap>,
+    mut materials: ResMut<Assets<PlaneCutMaterial>>,
+    mut nplane_materials: ResMut<Assets<NPlaneCutMaterial>>,
+) {
+    let event = trigger.event();
+    let Ok((handle, transform)) = handles.get_mut(event.target) else {
+        return;
+    };
+    let Some(state) = &handle.drag else {
+        return;
+    };
+    let Some(&ray) = ray_map.map.get(&RayId::new(state.camera, event.pointer_id)) else {
+        return;
+    };
+    let Some(coordinate) = state.frame.coordinate(ray, state.part) else {
+        return;
+    };
+    let delta = match state.part {
+        Part::Arrow => snap(coordinate - state.start, handle.snap_distance),
+        Part::Ring(_) => snap(coordinate - state.start, handle.snap_angle),
+    };
+    let motion = state.frame.motion(state.part, delta);
+    if let (Some(start), Some(mut transform)) = (state.transform, transform) {
+        *transform = motion * start;
+        return;
+    }
+    let normal = motion.rotation * state.frame.normal;
+    let mut plane = normal.extend(normal.dot(motion.transform_point(state.frame.center)));
+    if let Some(model) = &state.model {
+        plane = plane_world_to_local(plane, model);
+    }
+    match &handle.target {
+        Some(PlaneCutTarget::Single(target)) => {
+            if let Some(material) = materials.get_mut(target) {
+                material.extension.plane = plane;
+            }
+        }
+        Some(PlaneCutTarget::NPlane(target, index)) => {
+            if let Some((p, _)) = nplane_materials
+                .get_mut(target)
+                .and_then(|material| material.extension.planes_and_colors.get_mut(*index))
+            {
+                *p = plane;
+            }
+        }
+        None => {}
+    }
+}
+
+fn end_drag(
+    trigger: Trigger<Pointer<DragEnd>>,
+    mut handles: Query<HandleDataMut>,
+    materials: Res<Assets<PlaneCutMaterial>>,
+    nplane_materials: Res<Assets<NPlaneCutMaterial>>,
+    mut dragged: EventWriter<PlaneCutDragged>,
+) {
+    let entity = trigger.event().target;
+    let Ok((mut handle, cut, transform, global_transform)) = handles.get_mut(entity) else {
+        return;
+    };
+    let Some(state) = handle.drag.take() else {
+        return;
+    };
+    let plane = handle_frame(
+        &handle,
+        cut,
+        transform,
+        global_transform,
+        &materials,
+        &nplane_materials,
+    )
+    .map_or(state.plane, |(plane, _, _)| plane);
+    dragged.write(PlaneCutDragged { entity, plane });
+}
+
+fn draw_handles(
+    mut gizmos: Gizmos<PlaneCutHandleGizmos>,
+    handles: Query<HandleData>,
+    materials: Res<Assets<PlaneCutMaterial>>,
+    nplane_materials: Res<Assets<NPlaneCutMaterial>>,
+) {
+    for (handle, cut, transform, global_transform) in &handles {
+        let Some((_, frame, _)) = handle_frame(
+            handle,
+            cut,
+            transform,
+            global_transform,
+            &materials,
+            &nplane_materials,
+        ) else {
+            continue;
+        };
+        let active = handle.drag.as_ref().map(|state| state.part);
+        let color = |part: Part, color: Color| {
+            if active == Some(part) {
+                Color::WHITE
+            } else {
+                color
+            }
+        };
+        gizmos.arrow(
+            frame.center,
+            frame.center + frame.normal * handle.size,
+            color(Part::Arrow, basic::YELLOW.into()),
+        );
+        for (i, ring_color) in [basic::RED, basic::LIME].into_iter().enumerate() {
+            gizmos.circle(
+                Isometry3d::new(frame.center, Quat::from_rotation_arc(Vec3::Z, frame.axes[i])),
+                handle.size,
+                color(Part::Ring(i), ring_color.into()),
+            );
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use bevy::{
+    app::{App, PostUpdate},
+    asset::{AssetEvents, Assets, Handle},
+    ecs::{
+        component::Component,
+        reflect::ReflectComponent,
+        schedule::{IntoScheduleConfigs, SystemSet},
+        system::{Query, ResMut},
+    },
+    math::{Vec3, Vec4},
+    reflect::Reflect,
+    transform::{components::GlobalTransform, TransformSystem},
+};
+use super::*;
+use crate::nplane::NPlaneCutMaterial;
+
+pub(crate) fn plugin(app: &mut App) {
+    app.register_type::<PlaneCut>()
+        .configure_sets(
+            PostUpdate,
+            PlaneCutSystem::UpdatePlanes
+                .after(TransformSystem::TransformPropagate)
+                .before(AssetEvents),
+        )
+        .add_systems(PostUpdate, update_planes.in_set(PlaneCutSystem::UpdatePlanes));
+}
+
+/// System sets of the plane cut plugin.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PlaneCutSystem {
+    /// Write the plane of every [`PlaneCut`] into its target materials. Runs in
+    /// `PostUpdate` after transform propagation.
+    UpdatePlanes,
+}
+
+/// A material whose plane is driven by a [`PlaneCut`].
+#[derive(Reflect, Debug, Clone)]
+pub enum PlaneCutTarget {
+    /// Drive [`PlaneCutExt::plane`].
+    Single(Handle<PlaneCutMaterial>),
+    /// Drive the plane at the given index of
+    /// [`NPlaneCutExt::planes_and_colors`](crate::nplane::NPlaneCutExt::planes_and_colors).
+    NPlane(Handle<NPlaneCutMaterial>, usize),
+}
+
+impl From<Handle<PlaneCutMaterial>> for PlaneCutTarget {
+    fn from(handle: Handle<PlaneCutMaterial>) -> Self {
+        PlaneCutTarget::Single(handle)
+    }
+}
+
+impl From<Handle<NPlaneCutMaterial>> for PlaneCutTarget {
+    fn from(handle: Handle<NPlaneCutMaterial>) -> Self {
+        PlaneCutTarget::NPlane(handle, 0)
+    }
+}
+
+/// Place a plane cut with this entity's [`GlobalTransform`].
+///
+/// The plane passes through the entity's translation and its normal is the
+/// entity's local `normal` axis. Moving or rotating the entity moves the cut of
+/// every target material. The plane is in world space, so the targets ought to
+/// use [`Space::World`].
+#[derive(Component, Reflect, Debug, Clone)]
+#[reflect(Component)]
+pub struct PlaneCut {
+    /// The materials to drive.
+    pub targets: Vec<PlaneCutTarget>,
+    /// The normal of the plane in the entity's local space. The portion on
+    /// the negative side is cut. [`Vec3::Y`] is the default.
+    pub normal: Vec3,
+    /// Displace the plane along its normal in world units.
+    pub offset: f32,
+}
+
+impl Default for PlaneCut {
+    fn default() -> Self {
+        Self {
+            targets: Vec::new(),
+            normal: Vec3::Y,
+            offset: 0.0,
+        }
+    }
+}
+
+impl PlaneCut {
+    /// Create a plane cut that drives one material.
+    pub fn new(target: impl Into<PlaneCutTarget>) -> Self {
+        Self {
+            targets: vec![target.into()],
+            ..Default::default()
+        }
+    }
+
+    /// Add another material to drive.
+    pub fn with_target(mut self, target: impl Into<PlaneCutTarget>) -> Self {
+        self.targets.push(target.into());
+        self
+    }
+
+    /// Set the local normal axis.
+    pub fn with_normal(mut self, normal: Vec3) -> Self {
+        self.normal = normal;
+        self
+    }
+
+    /// Set the offset along the normal.
+    pub fn with_offset(mut self, offset: f32) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Return the world space plane _(nx, ny, nz, w)_ for the given transform.
+    ///
+    /// ```
+    /// # use bevy::prelude::*;
+    /// # use bevy_plane_cut::follow::PlaneCut;
+    /// let cut = PlaneCut::default().with_offset(0.5);
+    /// let transform = GlobalTransform::from_xyz(0.0, 2.0, 0.0);
+    /// assert_eq!(cut.plane(&transform), Vec4::new(0.0, 1.0, 0.0, 2.5));
+    /// ```
+    pub fn plane(&self, transform: &GlobalTransform) -> Vec4 {
+        let normal = (transform.rotation() * self.normal).normalize_or_zero();
+        let w = normal.dot(transform.translation()) + self.offset;
+        (normal, w).into()
+    }
+}
+
+fn update_planes(
+    q: Query<(&PlaneCut, &GlobalTransform)>,
+    mut materials: ResMut<Assets<PlaneCutMaterial>>,
+    mut nplane_materials: ResMut<Assets<NPlaneCutMaterial>>,
+) {
+    for (cut, transform) in &q {
+        let plane = cut.plane(transform);
+        for target in &cut.targets {
+            match target {
+                PlaneCutTarget::Single(handle) => {
+                    // Only touch the asset when the plane differs so it is
+                    // not re-uploaded every frame.
+                    if materials
+                        .get(handle)
+                        .is_some_and(|m| m.extension.plane != plane)
+                    {
+                        if let Some(m) = materials.get_mut(handle) {
+                            m.extension.plane = plane;
+                        }
+                    }
+                }
+                PlaneCutTarget::NPlane(handle, index) => {
+                    if nplane_materials
+                        .get(handle)
+                        .and_then(|m| m.extension.planes_and_colors.get(*index))
+                        .is_some_and(|(p, _)| *p != plane)
+                    {
+                        if let Some(m) = nplane_materials.get_mut(handle) {
+                            m.extension.planes_and_colors[*index].0 = plane;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,256 @@
+use bevy::{
+    app::{App, Plugin, PostUpdate},
+    asset::{Assets, UntypedAssetId},
+    color::Color,
+    ecs::{
+        schedule::IntoScheduleConfigs,
+        system::{Query, Res},
+    },
+    gizmos::{config::GizmoConfigGroup, gizmos::Gizmos, AppGizmoBuilder},
+    math::{Isometry3d, Mat3, Quat, URect, UVec2, Vec2, Vec3, Vec4, Vec4Swizzles},
+    pbr::MeshMaterial3d,
+    reflect::Reflect,
+    render::{camera::Camera, renderer::RenderDevice},
+    time::Time,
+    transform::{components::GlobalTransform, TransformSystem},
+};
+use super::*;
+use crate::{
+    follow::PlaneCutSystem,
+    nplane::{max_planes, NPlaneCutMaterial},
+};
+
+/// Draw the planes of every entity with a [`PlaneCutMaterial`] or
+/// [`NPlaneCutMaterial`] with gizmos, e.g., to debug where a cut is.
+///
+/// Each plane is a grid patch around its entity with an arrow along its normal
+/// toward the kept side, tinted with its cap's color. [`Space::Screen`] planes
+/// are lines across each camera at the entity's depth. Configure the gizmos
+/// with [`PlaneCutGizmos`] in Bevy's `GizmoConfigStore`.
+pub struct PlaneCutGizmoPlugin;
+
+impl Plugin for PlaneCutGizmoPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_gizmo_group::<PlaneCutGizmos>().add_systems(
+            PostUpdate,
+            (draw_planes, draw_nplane_planes)
+                .after(TransformSystem::TransformPropagate)
+                .after(PlaneCutSystem::UpdatePlanes),
+        );
+    }
+}
+
+/// The gizmos of the [`PlaneCutGizmoPlugin`].
+#[derive(Reflect, GizmoConfigGroup, Debug, Clone)]
+pub struct PlaneCutGizmos {
+    /// The side length of each plane's grid patch in world units. Two is the
+    /// default.
+    pub size: f32,
+    /// The number of grid cells along each side of the patch. Eight is the
+    /// default.
+    pub cells: u32,
+    /// The length of the normal arrow in world units, or a tenth of it in
+    /// pixels for screen space. 0.5 is the default.
+    pub normal_length: f32,
+    /// Draw only the planes of these materials, e.g.,
+    /// `handle.id().untyped()`. Empty, the default, draws every plane.
+    #[reflect(ignore)]
+    pub only: Vec<UntypedAssetId>,
+}
+
+impl Default for PlaneCutGizmos {
+    fn default() -> Self {
+        Self {
+            size: 2.0,
+            cells: 8,
+            normal_length: 0.5,
+            only: Vec::new(),
+        }
+    }
+}
+
+impl PlaneCutGizmos {
+    fn shows(&self, id: UntypedAssetId) -> bool {
+        self.only.is_empty() || self.only.contains(&id)
+    }
+}
+
+/// Return the segment of the line through `point` along `direction` inside a
+/// rectangle.
+fn clip_line(point: Vec2, direction: Vec2, min: Vec2, max: Vec2) -> Option<(Vec2, Vec2)> {
+    let mut t_min = f32::NEG_INFINITY;
+    let mut t_max = f32::INFINITY;
+    for i in 0..2 {
+        if direction[i].abs() < f32::EPSILON {
+            if point[i] < min[i] || point[i] > max[i] {
+                return None;
+            }
+        } else {
+            let a = (min[i] - point[i]) / direction[i];
+            let b = (max[i] - point[i]) / direction[i];
+            t_min = t_min.max(a.min(b));
+            t_max = t_max.min(a.max(b));
+        }
+    }
+    (t_min <= t_max).then(|| (point + direction * t_min, point + direction * t_max))
+}
+
+/// Draw a world space plane as a grid patch around the point nearest `near`.
+fn draw_plane(gizmos: &mut Gizmos<PlaneCutGizmos>, plane: Vec4, near: Vec3, color: Color) {
+    let length = plane.xyz().length();
+    if length == 0.0 || !length.is_finite() {
+        return;
+    }
+    let normal = plane.xyz() / length;
+    let center = near - normal * (normal.dot(near) - plane.w / length);
+    // The same axes as `contour::CrossSection`'s.
+    let x_axis = normal.any_orthonormal_vector();
+    let y_axis = x_axis.cross(normal);
+    let rotation = Quat::from_mat3(&Mat3::from_cols(x_axis, y_axis, -normal));
+    let PlaneCutGizmos {
+        size,
+        cells,
+        normal_length,
+        ..
+    } = *gizmos.config_ext;
+    let cells = cells.max(1);
+    gizmos.grid(
+        Isometry3d::new(center, rotation),
+        UVec2::splat(cells),
+        Vec2::splat(size / cells as f32),
+        color,
+    );
+    gizmos.arrow(center, center + normal * normal_length, color);
+}
+
+/// Draw a screen space plane as a line across a camera at the depth of
+/// `near`.
+fn draw_screen_plane(
+    gizmos: &mut Gizmos<PlaneCutGizmos>,
+    plane: Vec4,
+    near: Vec3,
+    color: Color,
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+) {
+    let (Some(rect), Some(ndc)) = (
+        camera.physical_viewport_rect(),
+        camera.world_to_ndc(camera_transform, near),
+    ) else {
+        return;
+    };
+    let URect { min, max } = rect;
+    let (min, max) = (min.as_vec2(), max.as_vec2());
+    // The shader tests the frag coord, which is in pixels with depth.
+    let normal = plane.xy();
+    let length_squared = normal.length_squared();
+    if length_squared == 0.0 {
+        return;
+    }
+    let c = plane.w - plane.z * ndc.z;
+    let point = normal * c / length_squared;
+    let Some((a, b)) = clip_line(point, normal.perp(), min, max) else {
+        return;
+    };
+    let to_world = |pixel: Vec2| {
+        let uv = (pixel - min) / (max - min);
+        camera.ndc_to_world(
+            camera_transform,
+            Vec3::new(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, ndc.z),
+        )
+    };
+    let arrow = normal / length_squared.sqrt() * gizmos.config_ext.normal_length * 10.0;
+    let middle = (a + b) / 2.0;
+    if let (Some(a), Some(b), Some(start), Some(end)) = (
+        to_world(a),
+        to_world(b),
+        to_world(middle),
+        to_world(middle + arrow),
+    ) {
+        gizmos.line(a, b, color);
+        gizmos.arrow(start, end, color);
+    }
+}
+
+/// Draw a plane in the space it is tested in. Like the shaders, it is moved
+/// `offset` along its normal after it is in that space.
+fn draw_space_plane(
+    gizmos: &mut Gizmos<PlaneCutGizmos>,
+    plane: Vec4,
+    offset: f32,
+    space: &Space,
+    transform: &GlobalTransform,
+    color: Color,
+    cameras: &Query<(&Camera, &GlobalTransform)>,
+) {
+    let near = transform.translation();
+    let mut plane = match space {
+        Space::Model => plane_local_to_world(plane, transform),
+        _ => plane,
+    };
+    plane.w += offset * plane.xyz().length();
+    match space {
+        Space::World | Space::Model => draw_plane(gizmos, plane, near, color),
+        Space::Screen => {
+            for (camera, camera_transform) in cameras {
+                if camera.is_active {
+                    draw_screen_plane(gizmos, plane, near, color, camera, camera_transform);
+                }
+            }
+        }
+    }
+}
+
+fn draw_planes(
+    mut gizmos: Gizmos<PlaneCutGizmos>,
+    q: Query<(&MeshMaterial3d<PlaneCutMaterial>, &GlobalTransform)>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    materials: Res<Assets<PlaneCutMaterial>>,
+    time: Res<Time>,
+) {
+    for (material, transform) in &q {
+        if !gizmos.config_ext.shows(material.id().untyped()) {
+            continue;
+        }
+        let Some(material) = materials.get(material) else {
+            continue;
+        };
+        let cut = &material.extension;
+        // A dissolve moves the plane by the shader's clock.
+        let offset = cut.dissolve.as_ref().map_or(0.0, |dissolve| {
+            dissolve.speed * dissolve.elapsed(time.elapsed_secs_wrapped())
+        });
+        draw_space_plane(
+            &mut gizmos,
+            cut.plane,
+            offset,
+            &cut.space,
+            transform,
+            cut.color,
+            &cameras,
+        );
+    }
+}
+
+fn draw_nplane_planes(
+    mut gizmos: Gizmos<PlaneCutGizmos>,
+    q: Query<(&MeshMaterial3d<NPlaneCutMaterial>, &GlobalTransform)>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    materials: Res<Assets<NPlaneCutMaterial>>,
+    render_device: Option<Res<RenderDevice>>,
+) {
+    for (material, transform) in &q {
+        if !gizmos.config_ext.shows(material.id().untyped()) {
+            continue;
+        }
+        let Some(material) = materials.get(material) else {
+            continue;
+        };
+        let cut = &material.extension;
+        // Only the planes the device cuts with.
+        let count = render_device.as_deref().and_then(max_planes).unwrap_or(usize::MAX);
+        for (plane, color) in cut.planes_and_colors.iter().take(count) {
+            draw_space_plane(&mut gizmos, *plane, 0.0, &cut.space, transform, *color, &cameras);
+        }
+    }
+}
@@ -4,8 +4,9 @@
 
 use bevy::{
     app::{App, Plugin},
-    asset::{embedded_asset, Asset},
-    math::Vec4,
+    asset::{embedded_asset, Asset, Handle},
+    image::Image,
+    math::{Vec2, Vec3, Vec3Swizzles, Vec4, Vec4Swizzles},
     pbr::{
         ExtendedMaterial, MaterialExtension, MaterialExtensionKey, MaterialExtensionPipeline,
         MaterialPlugin, StandardMaterial,
@@ -16,15 +17,38 @@ use bevy::{
         mesh::MeshVertexBufferLayoutRef,
         render_asset::RenderAssets,
         render_resource::{
-            AsBindGroup, AsBindGroupShaderType, RenderPipelineDescriptor, ShaderRef, ShaderType,
-            SpecializedMeshPipelineError,
+            AsBindGroup, AsBindGroupShaderType, Face, FragmentState, RenderPipelineDescriptor,
+            ShaderDefVal, ShaderRef, ShaderType, SpecializedMeshPipelineError,
         },
         texture::{GpuImage },
     },
+    transform::components::GlobalTransform,
 };
 
 /// Multiple plane cuts
 pub mod nplane;
+/// Drive plane cuts from entity transforms
+pub mod follow;
+/// Two plane cuts with their own space and shading
+pub mod double_plane;
+/// Boolean expressions of plane cuts
+pub mod csg;
+/// Cuts by spheres, boxes, cylinders, and capsules
+pub mod volume;
+/// Cuts by signed distance fields
+pub mod sdf;
+/// Split meshes by planes on the CPU
+pub mod slice;
+/// Cross sections of meshes by planes
+pub mod contour;
+/// Draw cut planes with gizmos
+pub mod gizmo;
+/// Drag cut planes with handles
+pub mod drag;
+/// Pick cut meshes where they are drawn
+pub mod pick;
+/// Ask whether points are cut on the CPU
+pub mod clip;
 
 /// Type alias for `ExtendedMaterial<StandardMaterial, PlaneCutExt>`.
 pub type PlaneCutMaterial = ExtendedMaterial<StandardMaterial, PlaneCutExt>;
@@ -35,28 +59,89 @@ pub struct PlaneCutPlugin;
 impl Plugin for PlaneCutPlugin {
     fn build(&self, app: &mut App) {
         embedded_asset!(app, "plane_cut.wgsl");
-        embedded_asset!(app, "double_plane_cut.wgsl");
         app.add_plugins(MaterialPlugin::<PlaneCutMaterial>::default());
         app.add_plugins(nplane::plugin);
+        app.add_plugins(double_plane::plugin);
+        app.add_plugins(csg::plugin);
+        app.add_plugins(volume::plugin);
+        app.add_plugins(sdf::plugin);
+        app.add_plugins(follow::plugin);
     }
 }
 
-/// Define what space to test the plane cut in: world space (default) or screen space.
-///
-/// TODO: Consider adding object/model space as an option.
+/// Define what space to test the plane cut in: world space (default), screen
+/// space, or model space.
 #[derive(Default, Reflect, Debug, Clone)]
 pub enum Space {
     /// Run plane cut in world space (default).
     #[default]
     World,
     /// Run plane cut in screen space. This turns the plane into more of a line cut.
+    ///
+    /// Note: shadow passes test the plane against the shadow map's screen,
+    /// not the camera's.
     Screen,
-    //Model
+    /// Run plane cut in the mesh's local space. The cut moves, rotates, and
+    /// scales with each entity that uses the material.
+    Model,
+}
+
+/// Which faces of a mesh a cut material draws.
+///
+/// A cut is drawn by showing the mesh's back faces as its caps. A mesh with a
+/// transparent or transmissive `StandardMaterial` blends its caps and surface
+/// in whatever order its triangles are in. To layer them, spawn the mesh
+/// twice with the same cut: once with [`CutFaces::Caps`] and an opaque base
+/// and once with [`CutFaces::Surface`] and the transparent base. The opaque
+/// caps then write depth and show through the surface, even through
+/// transmission. Bevy's order independent transparency sorts caps and surface
+/// by depth without this.
+///
+/// The faces are culled by their winding on screen. A transform with a
+/// negative scale mirrors the winding, so [`CutFaces::Caps`] draws such a
+/// mesh's surface and [`CutFaces::Surface`] its caps. The pipeline is
+/// specialized per material, not per entity, so give mirrored meshes their
+/// own material with the other variant.
+#[derive(Default, Reflect, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CutFaces {
+    /// Draw the surface and its caps (default).
+    #[default]
+    Both,
+    /// Draw only the back faces, which are the caps and the inner walls.
+    Caps,
+    /// Draw only the front faces.
+    Surface,
+}
+
+impl CutFaces {
+    /// Return the faces to cull.
+    fn cull_mode(self) -> Option<Face> {
+        match self {
+            CutFaces::Both => None,
+            CutFaces::Caps => Some(Face::Front),
+            CutFaces::Surface => Some(Face::Back),
+        }
+    }
+}
+
+/// Return the shader flags for a cut's space and shading.
+pub(crate) fn flags(space: &Space, shaded: bool) -> u32 {
+    let mut flags = 0;
+    match space {
+        Space::World => {}
+        Space::Screen => flags |= 1,
+        Space::Model => flags |= 2,
+    }
+    if shaded {
+        flags |= 4;
+    }
+    flags
 }
 
 /// The plane cut extension.
 #[derive(Asset, AsBindGroup, Reflect, Debug, Clone)]
 #[uniform(100, PlaneCutExtUniform)]
+#[bind_group_data(PlaneCutKey)]
 pub struct PlaneCutExt {
     /// The plane is defined with a normal vector _n_ and displacment scalar
     /// _w_, represented with a vector _(nx, ny, nz, w)_. Its equation is _n .
@@ -69,6 +154,37 @@ pub struct PlaneCutExt {
     /// Is the cut shaded or unlit? Shaded is the default. Note: using the
     /// deferred renderer will not respect an unlit option.
     pub shaded: bool,
+    /// Draw a glowing band where the surface meets the plane. None is the
+    /// default.
+    pub edge: Option<CutEdge>,
+    /// The width of a band on the kept side of the cut over which the surface
+    /// fades out toward the plane, in world units or pixels in screen space.
+    /// Opaque and masked surfaces fade by dithering, blended ones by alpha.
+    /// Zero, the default, is a hard cut.
+    pub soft_width: f32,
+    /// Break up the cut with noise that burns along its edge. None is the
+    /// default.
+    pub dissolve: Option<Dissolve>,
+    /// Draw a pattern over the cap's color. Solid is the default.
+    pub pattern: CapPattern,
+    /// An optional texture for the cap's color. It is multiplied by `color`
+    /// like `StandardMaterial::base_color_texture`.
+    #[texture(109)]
+    #[sampler(110)]
+    pub cap_texture: Option<Handle<Image>>,
+    /// An optional normal map for a shaded cap. Like
+    /// `StandardMaterial::normal_map_texture`, it must be in linear color
+    /// space. It is not used in screen space.
+    #[texture(111)]
+    #[sampler(112)]
+    pub cap_normal_map: Option<Handle<Image>>,
+    /// How the cap's textures are laid out on the plane.
+    pub cap_uv: CapUv,
+    /// The surface of a shaded cap. None, the default, takes the surface's
+    /// own.
+    pub cap_material: Option<CapMaterial>,
+    /// Which faces to draw. Both is the default.
+    pub faces: CutFaces,
 }
 
 impl Default for PlaneCutExt {
@@ -78,6 +194,552 @@ impl Default for PlaneCutExt {
             color: Color::BLACK,
             space: Space::default(),
             shaded: true,
+            edge: None,
+            soft_width: 0.0,
+            dissolve: None,
+            pattern: CapPattern::default(),
+            cap_texture: None,
+            cap_normal_map: None,
+            cap_uv: CapUv::default(),
+            cap_material: None,
+            faces: CutFaces::default(),
+        }
+    }
+}
+
+impl PlaneCutExt {
+    /// Return the plane in the space it is tested in like the shader's
+    /// `plane_cut_plane`. A model space plane is moved into world space, and a
+    /// dissolve moves the plane by its clock.
+    pub(crate) fn tested_plane(&self, transform: &GlobalTransform, time: f32) -> Vec4 {
+        let mut plane = match self.space {
+            Space::Model => plane_local_to_world(self.plane, transform),
+            _ => self.plane,
+        };
+        if let Some(dissolve) = &self.dissolve {
+            plane.w += dissolve.speed * dissolve.elapsed(time) * plane.xyz().length();
+        }
+        plane
+    }
+
+    /// Return the position a point is tested at and where a dissolve's noise
+    /// is sampled.
+    fn tested_position(&self, point: &CutPoint) -> (Vec3, Vec3) {
+        match self.space {
+            Space::World => (point.position, point.position),
+            Space::Screen => (point.frag_coord, point.frag_coord.xy().extend(0.0)),
+            Space::Model => (
+                point.position,
+                point.transform.affine().inverse().transform_point3(point.position),
+            ),
+        }
+    }
+
+    /// Return whether the shader cuts a point.
+    ///
+    /// ```
+    /// use bevy::prelude::*;
+    /// use bevy_plane_cut::PlaneCutExt;
+    /// let cut = PlaneCutExt {
+    ///     plane: Vec4::new(0.0, 1.0, 0.0, 0.5),
+    ///     ..default()
+    /// };
+    /// assert!(cut.is_clipped(Vec3::ZERO));
+    /// assert!(!cut.is_clipped(Vec3::Y));
+    /// ```
+    pub fn is_clipped(&self, point: impl Into<CutPoint>) -> bool {
+        let point = point.into();
+        let plane = self.tested_plane(&point.transform, point.time);
+        let (position, noise_position) = self.tested_position(&point);
+        let depth = self
+            .dissolve
+            .as_ref()
+            .map_or(0.0, |dissolve| dissolve.depth(noise_position))
+            * plane.xyz().length();
+        position.dot(plane.xyz()) < plane.w + depth
+    }
+
+    /// Return how far a point is from the cut like the shader, in world units
+    /// or pixels in screen space. It is negative where the point is cut.
+    pub fn distance(&self, point: impl Into<CutPoint>) -> f32 {
+        let point = point.into();
+        let plane = self.tested_plane(&point.transform, point.time);
+        let (position, noise_position) = self.tested_position(&point);
+        (position.dot(plane.xyz()) - plane.w) / plane.xyz().length()
+            - self
+                .dissolve
+                .as_ref()
+                .map_or(0.0, |dissolve| dissolve.depth(noise_position))
+    }
+}
+
+/// A point to test against a cut on the CPU like the shaders do.
+///
+/// The shaders test a world position, a frag coord in [`Space::Screen`], and
+/// move [`Space::Model`] planes and noise with the mesh's transform. A `Vec3`
+/// is the same point in every space with no transform at time zero, so it is
+/// a point in the space of the plane. [`clip::CutQuery`] fills in the rest for
+/// an entity.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CutPoint {
+    /// The world position.
+    pub position: Vec3,
+    /// The transform of the mesh.
+    pub transform: GlobalTransform,
+    /// The frag coord: the physical pixel and the NDC depth.
+    pub frag_coord: Vec3,
+    /// The shaders' clock, which is Bevy's wrapped elapsed seconds. It moves a
+    /// [`Dissolve`].
+    pub time: f32,
+    /// The most planes of an [`NPlaneCutExt`](nplane::NPlaneCutExt) the device
+    /// cuts with, from [`nplane::max_planes`]. `None` tests every plane.
+    pub max_planes: Option<usize>,
+}
+
+impl From<Vec3> for CutPoint {
+    fn from(point: Vec3) -> Self {
+        Self {
+            position: point,
+            transform: GlobalTransform::IDENTITY,
+            frag_coord: point,
+            time: 0.0,
+            max_planes: None,
+        }
+    }
+}
+
+/// A glowing band where the surface meets a cut, e.g., for a scanner effect.
+///
+/// The band is emissive, so it is lit the same in the forward and deferred
+/// renderers and blooms when bright enough.
+#[derive(Reflect, Debug, Clone)]
+pub struct CutEdge {
+    /// The width of the band from the plane in world units. In screen space it
+    /// is in pixels.
+    pub width: f32,
+    /// The emissive color of the band.
+    pub color: Color,
+    /// How the band fades from the plane to its width. The glow is _(1 -
+    /// distance / width)^falloff_, so zero is a solid band, one fades linearly
+    /// (default), and larger fades faster.
+    pub falloff: f32,
+}
+
+impl Default for CutEdge {
+    fn default() -> Self {
+        Self {
+            width: 0.05,
+            color: Color::WHITE,
+            falloff: 1.0,
+        }
+    }
+}
+
+/// The GPU representation of a [`CutEdge`]. No edge has zero width.
+#[derive(Clone, Default, ShaderType)]
+pub(crate) struct CutEdgeUniform {
+    color: Vec4,
+    width: f32,
+    falloff: f32,
+}
+
+impl From<&Option<CutEdge>> for CutEdgeUniform {
+    fn from(edge: &Option<CutEdge>) -> Self {
+        match edge {
+            Some(edge) => Self {
+                color: LinearRgba::from(edge.color).to_f32_array().into(),
+                width: edge.width.max(0.0),
+                falloff: edge.falloff.max(0.0),
+            },
+            None => Self::default(),
+        }
+    }
+}
+
+/// A dissolve that breaks up a cut with 3D noise and burns along its edge,
+/// e.g., for level transitions and holographic build-ins.
+///
+/// The noise pushes the cut up to `amplitude` past the plane. The plane moves
+/// along its normal at `speed` from `start` by the shader's clock, so an
+/// animated cut needs no updates. Lengths are in world units and
+/// pixels in screen space. The noise sticks to the mesh in model space and to
+/// the screen in screen space. The caps stay on the plane.
+#[derive(Reflect, Debug, Clone)]
+pub struct Dissolve {
+    /// How many noise features there are per unit. Four is the default.
+    pub scale: f32,
+    /// How far the noise pushes the cut past the plane. 0.2 is the default.
+    pub amplitude: f32,
+    /// How fast the plane moves along its normal per second. Positive cuts
+    /// more. Zero is the default.
+    pub speed: f32,
+    /// When the plane starts to move by the shader's clock, which is Bevy's
+    /// `Time::elapsed_secs_wrapped()`, e.g., its value when the dissolve is
+    /// put on a material. The clock wraps every hour by default, so a
+    /// dissolve may move for up to an hour. Zero is the default.
+    pub start: f32,
+    /// The width of the burning band at the cut's edge. 0.05 is the default.
+    pub edge_width: f32,
+    /// The emissive color of the burning band.
+    pub edge_color: Color,
+}
+
+impl Default for Dissolve {
+    fn default() -> Self {
+        Self {
+            scale: 4.0,
+            amplitude: 0.2,
+            speed: 0.0,
+            start: 0.0,
+            edge_width: 0.05,
+            edge_color: Color::linear_rgb(4.0, 1.0, 0.1),
+        }
+    }
+}
+
+/// The GPU representation of a [`Dissolve`]. No dissolve has zero amplitude
+/// and speed.
+#[derive(Clone, Default, ShaderType)]
+pub(crate) struct DissolveUniform {
+    edge_color: Vec4,
+    scale: f32,
+    amplitude: f32,
+    speed: f32,
+    edge_width: f32,
+    start: f32,
+}
+
+impl From<&Option<Dissolve>> for DissolveUniform {
+    fn from(dissolve: &Option<Dissolve>) -> Self {
+        match dissolve {
+            Some(dissolve) => Self {
+                edge_color: LinearRgba::from(dissolve.edge_color).to_f32_array().into(),
+                scale: dissolve.scale,
+                amplitude: dissolve.amplitude.max(0.0),
+                speed: dissolve.speed,
+                edge_width: dissolve.edge_width.max(0.0),
+                start: dissolve.start,
+            },
+            None => Self::default(),
+        }
+    }
+}
+
+/// The period of Bevy's wrapped clock by default in seconds.
+const CLOCK_WRAP_PERIOD: f32 = 3600.0;
+
+impl Dissolve {
+    /// Return how long the plane has moved at a time of the shader's clock
+    /// like the shader's `dissolve_elapsed`.
+    pub(crate) fn elapsed(&self, time: f32) -> f32 {
+        let elapsed = time - self.start;
+        if elapsed < 0.0 {
+            // The clock wrapped since the start.
+            elapsed + CLOCK_WRAP_PERIOD
+        } else {
+            elapsed
+        }
+    }
+
+    /// Return how far the noise pushes the cut past the plane at a position
+    /// like the shader's `plane_cut_dissolve_depth`.
+    pub(crate) fn depth(&self, position: Vec3) -> f32 {
+        if self.amplitude <= 0.0 {
+            return 0.0;
+        }
+        self.amplitude * dissolve_noise(position * self.scale)
+    }
+}
+
+/// WGSL's `mix()`.
+fn mix(a: f32, b: f32, t: f32) -> f32 {
+    a * (1.0 - t) + b * t
+}
+
+/// Return a pseudorandom number in [0, 1) for a lattice point like the
+/// shader's `hash_3d`.
+fn hash_3d(p: Vec3) -> f32 {
+    let mut q = (p * 0.1031).fract_gl();
+    q += q.dot(q.zyx() + 31.32);
+    let h = (q.x + q.y) * q.z;
+    h - h.floor()
+}
+
+/// Return smooth 3D value noise in [0, 1] like the shader's `value_noise_3d`.
+fn value_noise_3d(p: Vec3) -> f32 {
+    let i = p.floor();
+    let f = p.fract_gl();
+    let u = f * f * (3.0 - 2.0 * f);
+    let corner = |x: f32, y: f32, z: f32| hash_3d(i + Vec3::new(x, y, z));
+    let x00 = mix(corner(0.0, 0.0, 0.0), corner(1.0, 0.0, 0.0), u.x);
+    let x10 = mix(corner(0.0, 1.0, 0.0), corner(1.0, 1.0, 0.0), u.x);
+    let x01 = mix(corner(0.0, 0.0, 1.0), corner(1.0, 0.0, 1.0), u.x);
+    let x11 = mix(corner(0.0, 1.0, 1.0), corner(1.0, 1.0, 1.0), u.x);
+    mix(mix(x00, x10, u.y), mix(x01, x11, u.y), u.z)
+}
+
+/// Return three octaves of value noise in [0, 1] like the shader's
+/// `dissolve_noise`.
+fn dissolve_noise(p: Vec3) -> f32 {
+    (value_noise_3d(p) + 0.5 * value_noise_3d(p * 2.0) + 0.25 * value_noise_3d(p * 4.0)) / 1.75
+}
+
+/// Return a plane that is tested in model space in world space like the
+/// shaders do.
+pub(crate) fn plane_local_to_world(plane: Vec4, transform: &GlobalTransform) -> Vec4 {
+    let affine = transform.affine();
+    let normal = plane.xyz();
+    let point = normal * plane.w / normal.length_squared();
+    let world_point = affine.transform_point3(point);
+    let world_normal = (affine.matrix3.inverse().transpose() * normal).normalize();
+    world_normal.extend(world_normal.dot(world_point))
+}
+
+/// Return a world space plane in the model space of a transform, the inverse
+/// of [`plane_local_to_world`].
+pub(crate) fn plane_world_to_local(plane: Vec4, transform: &GlobalTransform) -> Vec4 {
+    plane_local_to_world(plane, &GlobalTransform::from(transform.affine().inverse()))
+}
+
+/// The kind of pattern drawn on a cap.
+#[derive(Default, Reflect, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hatch {
+    /// Only the cap's color (default).
+    #[default]
+    Solid,
+    /// Parallel lines like ANSI31 hatching.
+    Lines,
+    /// Two sets of parallel lines at right angles.
+    Crosshatch,
+    /// Dots on a square grid.
+    Dots,
+}
+
+impl Hatch {
+    fn index(self) -> u32 {
+        match self {
+            Hatch::Solid => 0,
+            Hatch::Lines => 1,
+            Hatch::Crosshatch => 2,
+            Hatch::Dots => 3,
+        }
+    }
+}
+
+/// A pattern drawn over a cap's color like the section hatching of
+/// engineering drawings.
+///
+/// The pattern is laid out in the plane's own 2D coordinates, the same as
+/// [`contour::CrossSection`]'s, so it stays put as the camera moves. They are
+/// in world units, in the mesh's units in model space, and in pixels in screen
+/// space.
+#[derive(Reflect, Debug, Clone)]
+pub struct CapPattern {
+    /// The kind of pattern. Solid is the default.
+    pub hatch: Hatch,
+    /// The angle of the lines in radians counterclockwise from the plane's x
+    /// axis. 45° is the default.
+    pub angle: f32,
+    /// The distance between lines or dots.
+    pub spacing: f32,
+    /// The width of the lines or the diameter of the dots.
+    pub line_width: f32,
+    /// The color of the lines or dots.
+    pub color: Color,
+}
+
+impl Default for CapPattern {
+    fn default() -> Self {
+        Self {
+            hatch: Hatch::default(),
+            angle: std::f32::consts::FRAC_PI_4,
+            spacing: 0.1,
+            line_width: 0.02,
+            color: Color::BLACK,
+        }
+    }
+}
+
+impl CapPattern {
+    /// Create a pattern of a kind with the default angle, spacing, line width,
+    /// and color.
+    pub fn new(hatch: Hatch) -> Self {
+        Self {
+            hatch,
+            ..Self::default()
+        }
+    }
+}
+
+/// The GPU representation of a [`CapPattern`].
+#[derive(Clone, Default, ShaderType)]
+pub(crate) struct CapPatternUniform {
+    color: Vec4,
+    angle: f32,
+    spacing: f32,
+    line_width: f32,
+    hatch: u32,
+}
+
+impl From<&CapPattern> for CapPatternUniform {
+    fn from(pattern: &CapPattern) -> Self {
+        Self {
+            color: LinearRgba::from(pattern.color).to_f32_array().into(),
+            angle: pattern.angle,
+            spacing: pattern.spacing,
+            line_width: pattern.line_width,
+            hatch: pattern.hatch.index(),
+        }
+    }
+}
+
+/// How a cap's textures are laid out on its plane.
+///
+/// The UVs come from the plane's 2D coordinates like [`CapPattern`]'s, so a
+/// cut brick wall shows bricks the same from every view. Textures are upright
+/// with _u_ along the plane's x axis and repeat whatever their sampler's
+/// address mode.
+#[derive(Reflect, Debug, Clone)]
+pub struct CapUv {
+    /// How many times the textures repeat per unit along each axis. One is the
+    /// default.
+    pub tiling: Vec2,
+    /// The offset of the textures in UV.
+    pub offset: Vec2,
+    /// The angle of the textures in radians counterclockwise.
+    pub rotation: f32,
+}
+
+impl Default for CapUv {
+    fn default() -> Self {
+        Self {
+            tiling: Vec2::ONE,
+            offset: Vec2::ZERO,
+            rotation: 0.0,
+        }
+    }
+}
+
+/// The GPU representation of a [`CapUv`].
+#[derive(Clone, Default, ShaderType)]
+pub(crate) struct CapUvUniform {
+    tiling: Vec2,
+    offset: Vec2,
+    rotation: f32,
+}
+
+impl From<&CapUv> for CapUvUniform {
+    fn from(uv: &CapUv) -> Self {
+        Self {
+            tiling: uv.tiling,
+            offset: uv.offset,
+            rotation: uv.rotation,
+        }
+    }
+}
+
+/// The surface of a shaded cap, e.g., a concrete interior in a chrome shell.
+///
+/// Without one a cap takes the metallic, roughness, and so on of the surface
+/// it cuts and only changes its color. The fields are the same as
+/// `StandardMaterial`'s. Like it, the deferred renderer has no clearcoat.
+#[derive(Reflect, Debug, Clone)]
+pub struct CapMaterial {
+    /// How metallic the cap is. Zero is the default.
+    pub metallic: f32,
+    /// How rough the cap is. 0.5 is the default.
+    pub perceptual_roughness: f32,
+    /// The specular reflectance of a nonmetallic cap. 0.5 is the default.
+    pub reflectance: f32,
+    /// The light the cap emits. Black is the default.
+    pub emissive: LinearRgba,
+    /// The strength of the clearcoat layer. Zero is the default.
+    pub clearcoat: f32,
+    /// How rough the clearcoat layer is. 0.5 is the default.
+    pub clearcoat_perceptual_roughness: f32,
+}
+
+impl Default for CapMaterial {
+    fn default() -> Self {
+        Self {
+            metallic: 0.0,
+            perceptual_roughness: 0.5,
+            reflectance: 0.5,
+            emissive: LinearRgba::BLACK,
+            clearcoat: 0.0,
+            clearcoat_perceptual_roughness: 0.5,
+        }
+    }
+}
+
+/// The GPU representation of an optional [`CapMaterial`].
+#[derive(Clone, Default, ShaderType)]
+pub(crate) struct CapMaterialUniform {
+    emissive: Vec4,
+    metallic: f32,
+    perceptual_roughness: f32,
+    reflectance: f32,
+    clearcoat: f32,
+    clearcoat_perceptual_roughness: f32,
+    enabled: u32,
+}
+
+impl From<&Option<CapMaterial>> for CapMaterialUniform {
+    fn from(material: &Option<CapMaterial>) -> Self {
+        match material {
+            Some(material) => Self {
+                emissive: material.emissive.to_vec4().with_w(0.0),
+                metallic: material.metallic,
+                perceptual_roughness: material.perceptual_roughness,
+                reflectance: material.reflectance,
+                clearcoat: material.clearcoat,
+                clearcoat_perceptual_roughness: material.clearcoat_perceptual_roughness,
+                enabled: 1,
+            },
+            None => Self::default(),
+        }
+    }
+}
+
+/// The pipeline key of a cut's faces and cap materials.
+///
+/// Bevy only lights clearcoat when the surface's `StandardMaterial` has one,
+/// so a cap with a clearcoat asks for it.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Hash)]
+pub struct PlaneCutKey {
+    clearcoat: bool,
+    faces: CutFaces,
+}
+
+impl PlaneCutKey {
+    pub(crate) fn new<'a>(
+        materials: impl IntoIterator<Item = &'a Option<CapMaterial>>,
+        faces: CutFaces,
+    ) -> Self {
+        Self {
+            clearcoat: materials
+                .into_iter()
+                .flatten()
+                .any(|material| material.clearcoat > 0.0),
+            faces,
+        }
+    }
+}
+
+impl From<&PlaneCutExt> for PlaneCutKey {
+    fn from(cut: &PlaneCutExt) -> Self {
+        Self::new([&cut.cap_material], cut.faces)
+    }
+}
+
+/// Cull the faces a cut does not draw and light the clearcoat of caps that
+/// have one.
+pub(crate) fn specialize_key(descriptor: &mut RenderPipelineDescriptor, key: PlaneCutKey) {
+    descriptor.primitive.cull_mode = key.faces.cull_mode();
+    let clearcoat_def: ShaderDefVal = "STANDARD_MATERIAL_CLEARCOAT".into();
+    if let Some(fragment) = descriptor.fragment.as_mut() {
+        if key.clearcoat && !fragment.shader_defs.contains(&clearcoat_def) {
+            fragment.shader_defs.push(clearcoat_def);
         }
     }
 }
@@ -88,21 +750,33 @@ struct PlaneCutExtUniform {
     plane: Vec4,
     color: Vec4,
     flags: u32,
+    edge: CutEdgeUniform,
+    soft_width: f32,
+    dissolve: DissolveUniform,
+    pattern: CapPatternUniform,
+    cap_uv: CapUvUniform,
+    cap_material: CapMaterialUniform,
 }
 
 impl AsBindGroupShaderType<PlaneCutExtUniform> for PlaneCutExt {
     fn as_bind_group_shader_type(&self, _images: &RenderAssets<GpuImage>) -> PlaneCutExtUniform {
-        let mut flags = 0;
-        if matches!(self.space, Space::Screen) {
-            flags |= 1;
+        let mut flags = flags(&self.space, self.shaded);
+        if self.cap_texture.is_some() {
+            flags |= 8;
         }
-        if self.shaded {
-            flags |= 4;
+        if self.cap_normal_map.is_some() {
+            flags |= 16;
         }
         PlaneCutExtUniform {
             plane: self.plane,
             color: LinearRgba::from(self.color).to_f32_array().into(),
             flags,
+            edge: (&self.edge).into(),
+            soft_width: self.soft_width.max(0.0),
+            dissolve: (&self.dissolve).into(),
+            pattern: (&self.pattern).into(),
+            cap_uv: (&self.cap_uv).into(),
+            cap_material: (&self.cap_material).into(),
         }
     }
 }
@@ -112,17 +786,58 @@ impl MaterialExtension for PlaneCutExt {
         "embedded://bevy_plane_cut/plane_cut.wgsl".into()
     }
 
+    fn prepass_fragment_shader() -> ShaderRef {
+        "embedded://bevy_plane_cut/plane_cut.wgsl".into()
+    }
+
     fn deferred_fragment_shader() -> ShaderRef {
         "embedded://bevy_plane_cut/plane_cut.wgsl".into()
     }
 
     fn specialize(
-        _pipeline: &MaterialExtensionPipeline,
+        pipeline: &MaterialExtensionPipeline,
         descriptor: &mut RenderPipelineDescriptor,
         _layout: &MeshVertexBufferLayoutRef,
-        _key: MaterialExtensionKey<Self>,
+        key: MaterialExtensionKey<Self>,
     ) -> Result<(), SpecializedMeshPipelineError> {
-        descriptor.primitive.cull_mode = None;
+        specialize_prepass(pipeline, descriptor);
+        specialize_key(descriptor, key.bind_group_data);
         Ok(())
     }
 }
+
+/// Make the depth, normal, and motion vector prepass respect the cut. The
+/// shadow pass is a depth prepass too.
+///
+/// Bevy skips the fragment stage of an opaque material's depth prepass, which
+/// would let the cut away portion write depth and cast shadows. So we add one
+/// and tell the shader with `PLANE_CUT_PREPASS` that it is not the deferred
+/// prepass, which shares the `PREPASS_PIPELINE` shader def.
+pub(crate) fn specialize_prepass(
+    pipeline: &MaterialExtensionPipeline,
+    descriptor: &mut RenderPipelineDescriptor,
+) {
+    let shader_defs = &descriptor.vertex.shader_defs;
+    if !shader_defs.contains(&"PREPASS_PIPELINE".into())
+        || shader_defs.contains(&"DEFERRED_PREPASS".into())
+    {
+        return;
+    }
+    let prepass_def: ShaderDefVal = "PLANE_CUT_PREPASS".into();
+    match descriptor.fragment.as_mut() {
+        Some(fragment) => fragment.shader_defs.push(prepass_def),
+        None => {
+            let Some(shader) = pipeline.fragment_shader.clone() else {
+                return;
+            };
+            let mut shader_defs = shader_defs.clone();
+            shader_defs.push(prepass_def);
+            descriptor.fragment = Some(FragmentState {
+                shader,
+                shader_defs,
+                entry_point: "fragment".into(),
+                targets: Vec::new(),
+            });
+        }
+    }
+}
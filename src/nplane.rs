@@ -7,22 +7,108 @@ use bevy::{
         MaterialPlugin, StandardMaterial,
     },
     color::{Color, LinearRgba, ColorToComponents},
+    log::warn,
     reflect::Reflect,
+    ecs::system::SystemParamItem,
     render::{
         mesh::MeshVertexBufferLayoutRef,
-        render_asset::RenderAssets,
         render_resource::{
-            AsBindGroup, AsBindGroupShaderType, RenderPipelineDescriptor, ShaderRef, ShaderType,
-            SpecializedMeshPipelineError,
+            encase::{StorageBuffer, UniformBuffer},
+            AsBindGroup, AsBindGroupError, BindGroupLayout, BindGroupLayoutEntry, BindingResources,
+            BindingType, BufferBindingType, BufferInitDescriptor, BufferUsages,
+            OwnedBindingResource, RenderPipelineDescriptor, ShaderRef, ShaderStages, ShaderType,
+            SpecializedMeshPipelineError, UnpreparedBindGroup,
         },
-        texture::{GpuImage },
+        renderer::RenderDevice,
     },
 };
+use std::fmt;
 use super::*;
 
-/// The maximum number of plane cuts.
+/// The maximum number of plane cuts on devices without storage buffers.
 pub const PLANE_MAX: usize = 6;
 
+/// The number of storage buffer bindings per shader stage a device must
+/// support for the planes to be stored in a storage buffer, which has no
+/// maximum. Bevy's own bindings use up to six, so this leaves room for ours.
+/// Devices with fewer fall back to a uniform limited to [`PLANE_MAX`] planes.
+///
+/// The shader checks the same number against `AVAILABLE_STORAGE_BUFFER_BINDINGS`.
+pub const STORAGE_BUFFER_BINDINGS_REQUIRED: u32 = 8;
+
+/// Does this device store the planes in a storage buffer?
+pub fn uses_storage_buffer(render_device: &RenderDevice) -> bool {
+    render_device.limits().max_storage_buffers_per_shader_stage
+        >= STORAGE_BUFFER_BINDINGS_REQUIRED
+}
+
+/// The maximum number of planes this device can cut with or `None` if there
+/// is no maximum.
+pub fn max_planes(render_device: &RenderDevice) -> Option<usize> {
+    (!uses_storage_buffer(render_device)).then_some(PLANE_MAX)
+}
+
+/// An invalid [`NPlaneCutExt`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NPlaneCutError {
+    /// There are more planes than the maximum.
+    TooManyPlanes {
+        /// The number of planes given.
+        count: usize,
+        /// The maximum number of planes.
+        max: usize,
+    },
+    /// A plane's normal has zero length, so it does not define a plane.
+    ZeroNormal {
+        /// The index of the plane.
+        index: usize,
+    },
+    /// A plane has a NaN or infinite component.
+    NotFinite {
+        /// The index of the plane.
+        index: usize,
+    },
+}
+
+impl fmt::Display for NPlaneCutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NPlaneCutError::TooManyPlanes { count, max } => {
+                write!(f, "{count} planes given but at most {max} are supported")
+            }
+            NPlaneCutError::ZeroNormal { index } => {
+                write!(f, "plane {index} has a zero length normal")
+            }
+            NPlaneCutError::NotFinite { index } => {
+                write!(f, "plane {index} has a NaN or infinite component")
+            }
+        }
+    }
+}
+
+impl std::error::Error for NPlaneCutError {}
+
+/// Check that there are no more than `max_planes` planes, if given, and that
+/// each plane is finite with a nonzero normal.
+pub(crate) fn validate_planes(
+    planes_and_colors: &[(Vec4, Color)],
+    max_planes: Option<usize>,
+) -> Result<(), NPlaneCutError> {
+    let count = planes_and_colors.len();
+    if let Some(max) = max_planes.filter(|max| count > *max) {
+        return Err(NPlaneCutError::TooManyPlanes { count, max });
+    }
+    for (index, (plane, _)) in planes_and_colors.iter().enumerate() {
+        if !plane.is_finite() {
+            return Err(NPlaneCutError::NotFinite { index });
+        }
+        if plane.truncate().length_squared() == 0.0 {
+            return Err(NPlaneCutError::ZeroNormal { index });
+        }
+    }
+    Ok(())
+}
+
 pub(crate) fn plugin(app: &mut App) {
     embedded_asset!(app, "nplane_cut.wgsl");
     app.add_plugins(MaterialPlugin::<NPlaneCutMaterial>::default());
@@ -31,9 +117,19 @@ pub(crate) fn plugin(app: &mut App) {
 /// Type alias for two plane cut material.
 pub type NPlaneCutMaterial = ExtendedMaterial<StandardMaterial, NPlaneCutExt>;
 
+/// How the planes of a [`NPlaneCutExt`] combine.
+#[derive(Default, Reflect, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Combine {
+    /// Keep only what every plane keeps (default). Any plane may cut.
+    #[default]
+    Intersection,
+    /// Keep what any plane keeps. Only what every plane cuts is removed, e.g.,
+    /// a quarter cutaway with two planes.
+    Union,
+}
+
 /// The plane cut extension.
-#[derive(Asset, AsBindGroup, Reflect, Debug, Clone)]
-#[uniform(101, NPlaneCutExtUniform)]
+#[derive(Asset, Reflect, Debug, Clone)]
 pub struct NPlaneCutExt {
     /// The plane is defined with a normal vector _n_ and displacment scalar
     /// _w_, represented with a vector _(nx, ny, nz, w)_. Its equation is _n .
@@ -41,13 +137,34 @@ pub struct NPlaneCutExt {
     ///
     /// Each vector has an associated color of its cut.
     ///
-    /// Respects a maximum of `PLANE_MAX`.
+    /// There is no maximum where storage buffers are available; otherwise
+    /// respects a maximum of `PLANE_MAX` and planes past it are ignored with a
+    /// warning. See [`max_planes`] and [`NPlaneCutExt::try_new`].
     pub planes_and_colors: Vec<(Vec4, Color)>,
     /// Define the space the plane is tested in.
     pub space: Space,
     /// Is the cut shaded or unlit? Shaded is the default. Note: using the
     /// deferred renderer will not respect an unlit option.
     pub shaded: bool,
+    /// How the planes combine. Intersection is the default.
+    pub combine: Combine,
+    /// Draw a glowing band where the surface meets the cut. None is the
+    /// default.
+    pub edge: Option<CutEdge>,
+    /// The width of a band on the kept side of the cut over which the surface
+    /// fades out toward the plane, in world units or pixels in screen space.
+    /// Opaque and masked surfaces fade by dithering, blended ones by alpha.
+    /// Zero, the default, is a hard cut.
+    pub soft_width: f32,
+    /// The pattern drawn over each plane's cap in the order of
+    /// `planes_and_colors`. Planes without one are solid. Empty is the default.
+    pub patterns: Vec<CapPattern>,
+    /// The surface of each plane's shaded cap in the order of
+    /// `planes_and_colors`. Planes without one take the surface's own. Empty
+    /// is the default.
+    pub cap_materials: Vec<Option<CapMaterial>>,
+    /// Which faces to draw. Both is the default.
+    pub faces: CutFaces,
 }
 
 impl Default for NPlaneCutExt {
@@ -56,41 +173,288 @@ impl Default for NPlaneCutExt {
             planes_and_colors: vec![(Vec4::new(1.0, 0.0, 0.0, 0.0), Color::BLACK)],
             space: Space::default(),
             shaded: true,
+            combine: Combine::default(),
+            edge: None,
+            soft_width: 0.0,
+            patterns: Vec::new(),
+            cap_materials: Vec::new(),
+            faces: CutFaces::default(),
         }
     }
 }
 
-/// The GPU representation of the uniform data of a [`PlaneCutExt`].
+/// The GPU representation of the uniform data of a [`NPlaneCutExt`].
+///
+/// When the planes are in a storage buffer, the arrays are left empty.
 #[derive(Clone, Default, ShaderType)]
 struct NPlaneCutExtUniform {
     planes: [Vec4; PLANE_MAX],
     colors: [Vec4; PLANE_MAX],
     flags: u32,
     count: u32,
+    edge: CutEdgeUniform,
+    soft_width: f32,
+    patterns: [CapPatternUniform; PLANE_MAX],
+    cap_materials: [CapMaterialUniform; PLANE_MAX],
+}
+
+/// The GPU representation of one plane in the storage buffer of a
+/// [`NPlaneCutExt`].
+#[derive(Clone, Default, ShaderType)]
+struct NPlaneCutPlane {
+    plane: Vec4,
+    color: Vec4,
+    pattern: CapPatternUniform,
+    cap_material: CapMaterialUniform,
 }
 
-impl AsBindGroupShaderType<NPlaneCutExtUniform> for NPlaneCutExt {
-    fn as_bind_group_shader_type(&self, _images: &RenderAssets<GpuImage>) -> NPlaneCutExtUniform {
-        let mut flags = 0;
-        if matches!(self.space, Space::Screen) {
-            flags |= 1;
+impl NPlaneCutExt {
+    /// Create a cut from its planes and colors with a world space and shaded
+    /// cut, or return why they are invalid.
+    ///
+    /// At most [`PLANE_MAX`] planes are accepted, which every device supports.
+    /// Use [`NPlaneCutExt::validate`] with [`max_planes`] to accept more on
+    /// devices with storage buffers.
+    ///
+    /// ```
+    /// use bevy::prelude::*;
+    /// use bevy_plane_cut::nplane::{NPlaneCutError, NPlaneCutExt};
+    /// assert!(NPlaneCutExt::try_new(vec![(Vec4::new(0.0, 1.0, 0.0, 0.5), Color::BLACK)]).is_ok());
+    /// assert_eq!(
+    ///     NPlaneCutExt::try_new(vec![(Vec4::W, Color::BLACK)]).unwrap_err(),
+    ///     NPlaneCutError::ZeroNormal { index: 0 }
+    /// );
+    /// ```
+    pub fn try_new(planes_and_colors: Vec<(Vec4, Color)>) -> Result<Self, NPlaneCutError> {
+        let cut = Self {
+            planes_and_colors,
+            ..Self::default()
+        };
+        cut.validate(Some(PLANE_MAX))?;
+        Ok(cut)
+    }
+
+    /// Check that there are no more than `max_planes` planes, if given, and
+    /// that each plane is finite with a nonzero normal.
+    pub fn validate(&self, max_planes: Option<usize>) -> Result<(), NPlaneCutError> {
+        validate_planes(&self.planes_and_colors, max_planes)
+    }
+
+    /// Return the planes in the space they are tested in like the shader's
+    /// `nplane_cut_plane`. Model space planes are moved into world space. Like
+    /// the uniform, planes past `max_planes` are left out.
+    pub(crate) fn tested_planes<'a>(
+        &'a self,
+        transform: &'a GlobalTransform,
+        max_planes: Option<usize>,
+    ) -> impl Iterator<Item = Vec4> + 'a {
+        self.planes_and_colors
+            .iter()
+            .take(max_planes.unwrap_or(usize::MAX))
+            .map(|(plane, _)| match self.space {
+                Space::Model => plane_local_to_world(*plane, transform),
+                _ => *plane,
+            })
+    }
+
+    /// Return the position a point is tested at.
+    fn tested_position(&self, point: &CutPoint) -> Vec3 {
+        match self.space {
+            Space::Screen => point.frag_coord,
+            _ => point.position,
+        }
+    }
+
+    /// Return whether the shader cuts a point: any plane cuts it for an
+    /// intersection and every plane for a union.
+    ///
+    /// ```
+    /// use bevy::prelude::*;
+    /// use bevy_plane_cut::nplane::{Combine, NPlaneCutExt};
+    /// let mut cut = NPlaneCutExt::try_new(vec![
+    ///     (Vec4::new(1.0, 0.0, 0.0, 0.0), Color::BLACK),
+    ///     (Vec4::new(0.0, 1.0, 0.0, 0.0), Color::BLACK),
+    /// ])
+    /// .unwrap();
+    /// assert!(cut.is_clipped(Vec3::new(-1.0, 1.0, 0.0)));
+    /// cut.combine = Combine::Union;
+    /// assert!(!cut.is_clipped(Vec3::new(-1.0, 1.0, 0.0)));
+    /// assert!(cut.is_clipped(Vec3::new(-1.0, -1.0, 0.0)));
+    /// ```
+    pub fn is_clipped(&self, point: impl Into<CutPoint>) -> bool {
+        let point = point.into();
+        let position = self.tested_position(&point);
+        let mut distances = self
+            .tested_planes(&point.transform, point.max_planes)
+            .map(|plane| position.dot(plane.xyz()) - plane.w);
+        match self.combine {
+            Combine::Intersection => distances.any(|distance| distance < 0.0),
+            Combine::Union => !distances.any(|distance| distance >= 0.0),
+        }
+    }
+
+    /// Return how far a point is from the cut like the shader, in world units
+    /// or pixels in screen space. It is negative where the point is cut. An
+    /// intersection is as near as its nearest plane and a union at least as
+    /// far as its farthest.
+    pub fn distance(&self, point: impl Into<CutPoint>) -> f32 {
+        let point = point.into();
+        let position = self.tested_position(&point);
+        let distances = self
+            .tested_planes(&point.transform, point.max_planes)
+            .map(|plane| (position.dot(plane.xyz()) - plane.w) / plane.xyz().length());
+        match self.combine {
+            Combine::Intersection => distances.fold(3e10, f32::min),
+            Combine::Union => distances.fold(-3e10, f32::max),
         }
-        if self.shaded {
-            flags |= 4;
+    }
+
+    /// Return plane `i`'s pattern. The default is solid.
+    fn pattern(&self, i: usize) -> CapPatternUniform {
+        self.patterns.get(i).map(Into::into).unwrap_or_default()
+    }
+
+    /// Return plane `i`'s cap material. The default takes the surface's.
+    fn cap_material(&self, i: usize) -> CapMaterialUniform {
+        self.cap_materials.get(i).map(Into::into).unwrap_or_default()
+    }
+
+    fn uniform(&self, storage: bool) -> NPlaneCutExtUniform {
+        let mut flags = flags(&self.space, self.shaded);
+        if self.combine == Combine::Union {
+            flags |= 8;
         }
         let mut planes = [Vec4::ZERO; PLANE_MAX];
         let mut colors = [Vec4::ZERO; PLANE_MAX];
-        for (i, (v, c)) in self.planes_and_colors.iter().enumerate() {
-            planes[i] = *v;
-            colors[i] = LinearRgba::from(*c).to_f32_array().into();
-        }
+        let mut patterns: [CapPatternUniform; PLANE_MAX] = Default::default();
+        let mut cap_materials: [CapMaterialUniform; PLANE_MAX] = Default::default();
+        let count = if storage {
+            self.planes_and_colors.len()
+        } else {
+            if self.planes_and_colors.len() > PLANE_MAX {
+                warn!(
+                    "NPlaneCutExt has {} planes but this device supports at most {}; ignoring the rest.",
+                    self.planes_and_colors.len(),
+                    PLANE_MAX
+                );
+            }
+            for (i, (v, c)) in self.planes_and_colors.iter().take(PLANE_MAX).enumerate() {
+                planes[i] = *v;
+                colors[i] = LinearRgba::from(*c).to_f32_array().into();
+                patterns[i] = self.pattern(i);
+                cap_materials[i] = self.cap_material(i);
+            }
+            self.planes_and_colors.len().min(PLANE_MAX)
+        };
         NPlaneCutExtUniform {
             planes,
             colors,
             flags,
-            count: self.planes_and_colors.len() as u32,
+            count: count as u32,
+            edge: (&self.edge).into(),
+            soft_width: self.soft_width.max(0.0),
+            patterns,
+            cap_materials,
         }
     }
+
+    fn storage(&self) -> Vec<NPlaneCutPlane> {
+        let mut planes: Vec<NPlaneCutPlane> = self
+            .planes_and_colors
+            .iter()
+            .enumerate()
+            .map(|(i, (v, c))| NPlaneCutPlane {
+                plane: *v,
+                color: LinearRgba::from(*c).to_f32_array().into(),
+                pattern: self.pattern(i),
+                cap_material: self.cap_material(i),
+            })
+            .collect();
+        if planes.is_empty() {
+            // A binding may not be empty.
+            planes.push(NPlaneCutPlane::default());
+        }
+        planes
+    }
+}
+
+// The bindings depend on the device, so this is not derived.
+impl AsBindGroup for NPlaneCutExt {
+    type Data = PlaneCutKey;
+    type Param = ();
+
+    fn label() -> Option<&'static str> {
+        Some("NPlaneCutExt")
+    }
+
+    fn unprepared_bind_group(
+        &self,
+        _layout: &BindGroupLayout,
+        render_device: &RenderDevice,
+        _param: &mut SystemParamItem<'_, '_, Self::Param>,
+        _force_no_bindless: bool,
+    ) -> Result<UnpreparedBindGroup<Self::Data>, AsBindGroupError> {
+        let storage = uses_storage_buffer(render_device);
+        let mut uniform = UniformBuffer::new(Vec::new());
+        uniform.write(&self.uniform(storage)).unwrap();
+        let mut bindings = vec![(
+            101,
+            OwnedBindingResource::Buffer(render_device.create_buffer_with_data(
+                &BufferInitDescriptor {
+                    label: Some("nplane_cut_uniform"),
+                    usage: BufferUsages::COPY_DST | BufferUsages::UNIFORM,
+                    contents: uniform.as_ref(),
+                },
+            )),
+        )];
+        if storage {
+            let mut buffer = StorageBuffer::new(Vec::new());
+            buffer.write(&self.storage()).unwrap();
+            bindings.push((
+                103,
+                OwnedBindingResource::Buffer(render_device.create_buffer_with_data(
+                    &BufferInitDescriptor {
+                        label: Some("nplane_cut_planes"),
+                        usage: BufferUsages::COPY_DST | BufferUsages::STORAGE,
+                        contents: buffer.as_ref(),
+                    },
+                )),
+            ));
+        }
+        Ok(UnpreparedBindGroup {
+            bindings: BindingResources(bindings),
+            data: PlaneCutKey::new(&self.cap_materials, self.faces),
+        })
+    }
+
+    fn bind_group_layout_entries(
+        render_device: &RenderDevice,
+        _force_no_bindless: bool,
+    ) -> Vec<BindGroupLayoutEntry> {
+        let mut entries = vec![BindGroupLayoutEntry {
+            binding: 101,
+            visibility: ShaderStages::all(),
+            ty: BindingType::Buffer {
+                ty: BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: Some(NPlaneCutExtUniform::min_size()),
+            },
+            count: None,
+        }];
+        if uses_storage_buffer(render_device) {
+            entries.push(BindGroupLayoutEntry {
+                binding: 103,
+                visibility: ShaderStages::all(),
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Storage { read_only: true },
+                    has_dynamic_offset: false,
+                    min_binding_size: Some(NPlaneCutPlane::min_size()),
+                },
+                count: None,
+            });
+        }
+        entries
+    }
 }
 
 impl MaterialExtension for NPlaneCutExt {
@@ -98,17 +462,22 @@ impl MaterialExtension for NPlaneCutExt {
         "embedded://bevy_plane_cut/nplane_cut.wgsl".into()
     }
 
+    fn prepass_fragment_shader() -> ShaderRef {
+        "embedded://bevy_plane_cut/nplane_cut.wgsl".into()
+    }
+
     fn deferred_fragment_shader() -> ShaderRef {
         "embedded://bevy_plane_cut/nplane_cut.wgsl".into()
     }
 
     fn specialize(
-        _pipeline: &MaterialExtensionPipeline,
+        pipeline: &MaterialExtensionPipeline,
         descriptor: &mut RenderPipelineDescriptor,
         _layout: &MeshVertexBufferLayoutRef,
-        _key: MaterialExtensionKey<Self>,
+        key: MaterialExtensionKey<Self>,
     ) -> Result<(), SpecializedMeshPipelineError> {
-        descriptor.primitive.cull_mode = None;
+        specialize_prepass(pipeline, descriptor);
+        specialize_key(descriptor, key.bind_group_data);
         Ok(())
     }
 }
@@ -2,7 +2,18 @@
 
     mesh_view_bindings::view,
     pbr_fragment::pbr_input_from_standard_material,
+    pbr_types::{
+        StandardMaterial, STANDARD_MATERIAL_FLAGS_DOUBLE_SIDED_BIT,
+        STANDARD_MATERIAL_FLAGS_ALPHA_MODE_RESERVED_BITS, STANDARD_MATERIAL_FLAGS_ALPHA_MODE_OPAQUE,
+        STANDARD_MATERIAL_FLAGS_ALPHA_MODE_MASK,
+    },
     pbr_functions::alpha_discard,
+    mesh_functions::{
+        get_world_from_local, get_local_from_world, get_previous_world_from_local,
+        mesh_normal_local_to_world, sign_determinant_model_3x3m,
+    },
+    mesh_bindings::mesh,
+    view_transformations::{frag_coord_to_ndc, position_ndc_to_world, position_world_to_ndc},
         // mesh::vertex,
 }
 
@@ -10,25 +21,109 @@
 #import bevy_pbr::{
     prepass_io::{VertexOutput, FragmentOutput},
     pbr_deferred_functions::deferred_output,
+    pbr_prepass_functions::{prepass_alpha_discard, calculate_motion_vector},
 }
 #else
 #import bevy_pbr::{
     forward_io::{VertexOutput, FragmentOutput},
     pbr_functions::{apply_pbr_lighting, main_pass_post_lighting_processing},
 }
+#ifdef OIT_ENABLED
+#import bevy_core_pipeline::oit::oit_draw
 #endif
+#endif
+struct CutEdge {
+    color: vec4<f32>,
+    width: f32,
+    falloff: f32,
+}
+
+struct CapMaterial {
+    emissive: vec4<f32>,
+    metallic: f32,
+    perceptual_roughness: f32,
+    reflectance: f32,
+    clearcoat: f32,
+    clearcoat_perceptual_roughness: f32,
+    enabled: u32,
+}
+
+struct CapPattern {
+    color: vec4<f32>,
+    angle: f32,
+    spacing: f32,
+    line_width: f32,
+    hatch: u32,
+}
+const CAP_PATTERN_SOLID: u32 = 0u;
+const CAP_PATTERN_LINES: u32 = 1u;
+const CAP_PATTERN_CROSSHATCH: u32 = 2u;
+const CAP_PATTERN_DOTS: u32 = 3u;
+
 struct NPlaneCutExt {
     plane: array<vec4<f32>, 6>,
     color: array<vec4<f32>, 6>,
     flags: u32,
     count: u32,
+    edge: CutEdge,
+    soft_width: f32,
+    pattern: array<CapPattern, 6>,
+    cap_material: array<CapMaterial, 6>,
 }
 const PLANE_CUT_FLAGS_SCREENSPACE_BIT: u32 = 1u;
+const PLANE_CUT_FLAGS_MODELSPACE_BIT: u32 = 2u;
 const PLANE_CUT_FLAGS_SHADED_BIT: u32 = 4u;
+const PLANE_CUT_FLAGS_UNION_BIT: u32 = 8u;
 
 @group(2) @binding(101)
 var<uniform> plane_cut_ext: NPlaneCutExt;
 
+#if AVAILABLE_STORAGE_BUFFER_BINDINGS >= 8
+// Same as nplane::STORAGE_BUFFER_BINDINGS_REQUIRED. The planes and colors are
+// in a storage buffer, so there is no PLANE_MAX.
+struct NPlaneCutPlane {
+    plane: vec4<f32>,
+    color: vec4<f32>,
+    pattern: CapPattern,
+    cap_material: CapMaterial,
+}
+
+@group(2) @binding(103)
+var<storage> nplane_cut_planes: array<NPlaneCutPlane>;
+
+fn nplane_cut_raw_plane(i: u32) -> vec4<f32> {
+    return nplane_cut_planes[i].plane;
+}
+
+fn nplane_cut_color(i: u32) -> vec4<f32> {
+    return nplane_cut_planes[i].color;
+}
+
+fn nplane_cut_pattern(i: u32) -> CapPattern {
+    return nplane_cut_planes[i].pattern;
+}
+
+fn nplane_cut_cap_material(i: u32) -> CapMaterial {
+    return nplane_cut_planes[i].cap_material;
+}
+#else
+fn nplane_cut_raw_plane(i: u32) -> vec4<f32> {
+    return plane_cut_ext.plane[i];
+}
+
+fn nplane_cut_color(i: u32) -> vec4<f32> {
+    return plane_cut_ext.color[i];
+}
+
+fn nplane_cut_pattern(i: u32) -> CapPattern {
+    return plane_cut_ext.pattern[i];
+}
+
+fn nplane_cut_cap_material(i: u32) -> CapMaterial {
+    return plane_cut_ext.cap_material[i];
+}
+#endif
+
 // @vertex
 // fn my_vertex(vertex_no_morph: Vertex) -> VertexOutput {
 //     return vertex(vertex_no_morph);
@@ -48,34 +143,120 @@ fn intersect_plane_line(plane: vec4<f32>, v: vec3<f32>, r: vec3<f32>) -> f32 {
     return t;
 }
 
-@fragment
-fn fragment(
-    in_: VertexOutput,
-    @builtin(front_facing) is_front: bool,
-) -> FragmentOutput {
-    var in = in_;
+// Convert a plane in the mesh's local space into a plane in world space.
+fn plane_local_to_world(plane: vec4<f32>, instance_index: u32) -> vec4<f32> {
+    let point = plane.xyz * plane.w / dot(plane.xyz, plane.xyz);
+    let world_point = (get_world_from_local(instance_index) * vec4(point, 1.0)).xyz;
+    let world_normal = mesh_normal_local_to_world(plane.xyz, instance_index);
+    return vec4(world_normal, dot(world_normal, world_point));
+}
 
-    let shaded = (plane_cut_ext.flags & PLANE_CUT_FLAGS_SHADED_BIT) != 0u;
+// Return plane `i` in the space it is tested in.
+fn nplane_cut_plane(i: u32, instance_index: u32) -> vec4<f32> {
+    if ((plane_cut_ext.flags & PLANE_CUT_FLAGS_MODELSPACE_BIT) != 0u) {
+        // Object space planes are moved into world space with the mesh so the
+        // rest of the shader only has to deal with world space.
+        return plane_local_to_world(nplane_cut_raw_plane(i), instance_index);
+    }
+    return nplane_cut_raw_plane(i);
+}
+
+// The union of the planes only removes the region every plane cuts, which is
+// convex. Discard the fragment if it is in that region. Otherwise return the
+// index of the plane through which the view ray leaves that region, which is
+// the cap a back face shows, or -1 if the view ray does not pass through it.
+// The view ray starts at `ray_origin` and is in the space of the planes.
+fn nplane_cut_union(
+    position: vec3<f32>,
+    ray_origin: vec3<f32>,
+    view_ray: vec3<f32>,
+    instance_index: u32,
+    is_front: bool,
+) -> i32 {
+    var inside = true;
+    for (var i: u32 = 0u; i < plane_cut_ext.count; i++) {
+        let plane = nplane_cut_plane(i, instance_index);
+        if dot(position, plane.xyz) - plane.w >= 0.0 {
+            inside = false;
+            break;
+        }
+    }
+    if inside {
+        discard;
+    }
+    if is_front {
+        return -1;
+    }
+    // Clip the view ray against every plane's cut half-space. Where two caps
+    // meet at a concave edge, the nearest exit picks the cap that is in front.
+    var plane_index: i32 = -1;
+    var t_enter = 0.0;
+    var t_exit = 3e10;
+    for (var i: u32 = 0u; i < plane_cut_ext.count; i++) {
+        let plane = nplane_cut_plane(i, instance_index);
+        let denom = dot(plane.xyz, view_ray);
+        let eye_dist = dot(plane.xyz, ray_origin) - plane.w;
+        if abs(denom) < 1e-6 {
+            // Parallel: the ray is cut by this plane everywhere or nowhere.
+            if eye_dist >= 0.0 {
+                return -1;
+            }
+            continue;
+        }
+        let t = -eye_dist / denom;
+        if denom > 0.0 {
+            // Leaving the cut half-space.
+            if t < t_exit {
+                t_exit = t;
+                plane_index = i32(i);
+            }
+        } else {
+            // Entering the cut half-space.
+            t_enter = max(t_enter, t);
+        }
+    }
+    if t_enter >= t_exit {
+        return -1;
+    }
+    return plane_index;
+}
 
+// Discard the fragment if it is cut. Otherwise return the index of the plane
+// whose cap a back face shows or -1.
+fn nplane_cut(
+    frag_coord: vec4<f32>,
+    world_position: vec4<f32>,
+    instance_index: u32,
+    is_front: bool,
+) -> i32 {
     var plane_index: i32 = -1;
     var t_max: f32 = 0;
     var position: vec3<f32>;
     if ((plane_cut_ext.flags & PLANE_CUT_FLAGS_SCREENSPACE_BIT) != 0u) {
         // Screenspace
-        position = in.position.xyz;
+        position = frag_coord.xyz;
     } else {
-        // World space
-        position = in.world_position.xyz;
+        // World or object space
+        position = world_position.xyz;
     }
 
+    let view_ray = normalize(world_position.xyz - view.world_position);
+    if ((plane_cut_ext.flags & PLANE_CUT_FLAGS_UNION_BIT) != 0u) {
+        if ((plane_cut_ext.flags & PLANE_CUT_FLAGS_SCREENSPACE_BIT) != 0u) {
+            // In frag coords the view ray runs from the near plane, which is
+            // at depth one, along the pixel to smaller depths.
+            return nplane_cut_union(position, vec3(frag_coord.xy, 1.0), vec3(0.0, 0.0, -1.0), instance_index, is_front);
+        }
+        return nplane_cut_union(position, view.world_position, view_ray, instance_index, is_front);
+    }
     var back_discard = false;
     var back_hits = 0;
     var front_hits = 0;
-    let view_ray = normalize(in.world_position.xyz - view.world_position);
     for (var i: u32 = 0u; i < plane_cut_ext.count; i++) {
     // for (var i: u32 = 1u; i < plane_cut_ext.count; i++) {
     // for (var i: u32 = 0u; i < 1; i++) {
-        let dist = dot(position, plane_cut_ext.plane[i].xyz) - plane_cut_ext.plane[i].w;
+        let plane = nplane_cut_plane(i, instance_index);
+        let dist = dot(position, plane.xyz) - plane.w;
         if dist < 0 {
             if is_front {
                 discard;
@@ -83,13 +264,13 @@ fn fragment(
                 back_discard = true;
             }
         }
-        let t = intersect_plane_line(plane_cut_ext.plane[i], view_ray, view.world_position);
+        let t = intersect_plane_line(plane, view_ray, view.world_position);
         if t != t || t < 0.0 {
             // t is NaN, no hit.
             continue;
         }
         // Make sure the view ray and the plane are anti-collinear
-        if dot(view_ray, plane_cut_ext.plane[i].xyz) > 0 {
+        if dot(view_ray, plane.xyz) > 0 {
             if t > t_max {
                 plane_index = i32(i);
                 t_max = t;
@@ -111,12 +292,13 @@ fn fragment(
         for (var i: u32 = 0u; i < plane_cut_ext.count; i++) {
         // for (var i: u32 = 0u; i < 1; i++) {
         // for (var i: u32 = 1u; i < plane_cut_ext.count; i++) {
-            let t = intersect_plane_line(plane_cut_ext.plane[i], -view_ray, position);
+            let plane = nplane_cut_plane(i, instance_index);
+            let t = intersect_plane_line(plane, -view_ray, position);
             if t != t || t < 0.0 {
                 continue;
             }
             hits++;
-            if dot(view_ray, plane_cut_ext.plane[i].xyz) > 0 {
+            if dot(view_ray, plane.xyz) > 0 {
                 if t > t_front {
                     t_front = t;
                 }
@@ -133,42 +315,442 @@ fn fragment(
             discard;
         }
     }
+    return plane_index;
+}
+
+// Return the glow of the edge band at a distance from the cut.
+fn cut_edge_glow(edge: CutEdge, distance: f32) -> vec3<f32> {
+    if (distance < 0.0 || distance >= edge.width) {
+        return vec3(0.0);
+    }
+    return edge.color.rgb * pow(1.0 - distance / edge.width, edge.falloff);
+}
+
+// Return how far a kept position is from the cut in the units the planes are
+// tested in. An intersection is as near as its nearest plane. A union's cut
+// region is convex, so it is at least as far as its farthest plane.
+fn nplane_cut_distance(
+    frag_coord: vec4<f32>,
+    world_position: vec4<f32>,
+    instance_index: u32,
+) -> f32 {
+    var position = world_position.xyz;
+    if ((plane_cut_ext.flags & PLANE_CUT_FLAGS_SCREENSPACE_BIT) != 0u) {
+        position = frag_coord.xyz;
+    }
+    let is_union = (plane_cut_ext.flags & PLANE_CUT_FLAGS_UNION_BIT) != 0u;
+    var distance = select(3e10, -3e10, is_union);
+    for (var i: u32 = 0u; i < plane_cut_ext.count; i++) {
+        let plane = nplane_cut_plane(i, instance_index);
+        let d = (dot(position, plane.xyz) - plane.w) / length(plane.xyz);
+        distance = select(min(distance, d), max(distance, d), is_union);
+    }
+    return distance;
+}
+
+// Return how opaque a kept surface is at a distance from the plane of a soft
+// cut. It fades in over the width.
+fn soft_cut_opacity(width: f32, distance: f32) -> f32 {
+    if (width <= 0.0) {
+        return 1.0;
+    }
+    return saturate(distance / width);
+}
+
+// Does screen-door transparency drop a fragment of this opacity? The 4x4 Bayer
+// pattern is fixed to the screen, so the prepass drops the same fragments as
+// the main pass.
+fn is_dithered_out(frag_coord: vec4<f32>, opacity: f32) -> bool {
+    var bayer = array<f32, 16>(
+        0.0, 8.0, 2.0, 10.0,
+        12.0, 4.0, 14.0, 6.0,
+        3.0, 11.0, 1.0, 9.0,
+        15.0, 7.0, 13.0, 5.0,
+    );
+    let p = vec2<u32>(frag_coord.xy) % 4u;
+    return opacity < (bayer[p.y * 4u + p.x] + 0.5) / 16.0;
+}
+
+// Does the material fade by alpha rather than by dithering?
+fn is_alpha_blended(material: StandardMaterial) -> bool {
+    let alpha_mode = material.flags & STANDARD_MATERIAL_FLAGS_ALPHA_MODE_RESERVED_BITS;
+    return alpha_mode != STANDARD_MATERIAL_FLAGS_ALPHA_MODE_OPAQUE
+        && alpha_mode != STANDARD_MATERIAL_FLAGS_ALPHA_MODE_MASK;
+}
+
+// Return how opaque the surface is at a kept fragment of a soft cut.
+fn nplane_cut_soft_opacity(in: VertexOutput) -> f32 {
+    let distance = nplane_cut_distance(in.position, in.world_position, in.instance_index);
+    return soft_cut_opacity(plane_cut_ext.soft_width, distance);
+}
+
+// Return where the view ray through a frag coord hits a world space plane.
+fn plane_ray_hit(xy: vec2<f32>, plane: vec4<f32>) -> vec3<f32> {
+    let ndc = frag_coord_to_ndc(vec4(xy, 1.0, 1.0)).xy;
+    let a = position_ndc_to_world(vec3(ndc, 1.0));
+    let b = position_ndc_to_world(vec3(ndc, 0.5));
+    return a + (b - a) * (plane.w - dot(plane.xyz, a)) / dot(plane.xyz, b - a);
+}
 
-    if (!is_front && shaded && plane_index >= 0) {
-        // The in.world_position is not actually correct, but I don't see any
-        // difference visually.
-        //
-        // We're drawing the backface, so it must be one of the planes, but we
-        // don't know which yet.
-        in.world_normal = -plane_cut_ext.plane[plane_index].xyz;
+// Return a point's 2D coordinates on a plane. The axes are the same as
+// `contour::CrossSection`'s.
+fn plane_2d(plane: vec4<f32>, position: vec3<f32>) -> vec2<f32> {
+    let n = normalize(plane.xyz);
+    // glam's Vec3::any_orthonormal_vector()
+    let sign = select(-1.0, 1.0, n.z >= 0.0);
+    let a = -1.0 / (sign + n.z);
+    let x_axis = vec3(n.x * n.y * a, sign + n.y * n.y * a, -n.y);
+    let y_axis = cross(x_axis, n);
+    let d = position - n * plane.w / length(plane.xyz);
+    return vec2(dot(d, x_axis), dot(d, y_axis));
+}
+
+// Return how much of a pixel a pattern's lines or dots cover at a point in
+// the plane's 2D coordinates. A pixel is `pixel` wide there.
+fn cap_pattern_coverage(pattern: CapPattern, p: vec2<f32>, pixel: f32) -> f32 {
+    let c = cos(pattern.angle);
+    let s = sin(pattern.angle);
+    // Rotate the lines onto the x axis.
+    let q = vec2(c * p.x + s * p.y, c * p.y - s * p.x) / pattern.spacing;
+    // The distance to the nearest grid line along each axis.
+    let d = abs(fract(q + 0.5) - 0.5) * pattern.spacing;
+    let r = pattern.line_width * 0.5;
+    let aa = max(pixel, 1e-6) * 0.5;
+    switch pattern.hatch {
+        case CAP_PATTERN_LINES: {
+            return 1.0 - smoothstep(r - aa, r + aa, d.y);
+        }
+        case CAP_PATTERN_CROSSHATCH: {
+            return 1.0 - smoothstep(r - aa, r + aa, min(d.x, d.y));
+        }
+        case CAP_PATTERN_DOTS: {
+            return 1.0 - smoothstep(r - aa, r + aa, length(d));
+        }
+        default: {
+            return 0.0;
+        }
     }
-    // Generate a PbrInput struct from the StandardMaterial bindings
-    var pbr_input = pbr_input_from_standard_material(in, is_front);
+}
 
-    if (!is_front && shaded && plane_index >= 0) {
-        pbr_input.material.base_color = plane_cut_ext.color[plane_index];
+// Return where plane `i`'s cap is at a frag coord in its 2D coordinates.
+fn nplane_cut_cap_2d(i: u32, frag_coord: vec3<f32>, instance_index: u32) -> vec2<f32> {
+    let raw_plane = nplane_cut_raw_plane(i);
+    if ((plane_cut_ext.flags & PLANE_CUT_FLAGS_SCREENSPACE_BIT) != 0u) {
+        return plane_2d(raw_plane, frag_coord);
+    }
+    let hit = plane_ray_hit(frag_coord.xy, nplane_cut_plane(i, instance_index));
+    if ((plane_cut_ext.flags & PLANE_CUT_FLAGS_MODELSPACE_BIT) != 0u) {
+        let local_hit = (get_local_from_world(instance_index) * vec4(hit, 1.0)).xyz;
+        return plane_2d(raw_plane, local_hit);
+    }
+    return plane_2d(raw_plane, hit);
+}
+
+// Return plane `i`'s cap color with its pattern at a frag coord.
+fn nplane_cut_cap_color(i: u32, frag_coord: vec4<f32>, instance_index: u32) -> vec4<f32> {
+    let pattern = nplane_cut_pattern(i);
+    if (pattern.hatch == CAP_PATTERN_SOLID || pattern.spacing <= 0.0) {
+        return nplane_cut_color(i);
+    }
+    // Find the size of a pixel from its neighbors rather than derivatives,
+    // which need uniform control flow.
+    let p = nplane_cut_cap_2d(i, frag_coord.xyz, instance_index);
+    let dx = nplane_cut_cap_2d(i, frag_coord.xyz + vec3(1.0, 0.0, 0.0), instance_index) - p;
+    let dy = nplane_cut_cap_2d(i, frag_coord.xyz + vec3(0.0, 1.0, 0.0), instance_index) - p;
+    let coverage = cap_pattern_coverage(pattern, p, max(length(dx), length(dy)));
+    return mix(nplane_cut_color(i), pattern.color, coverage);
+}
+
+// Give a cap its own surface if it has one.
+fn cap_material_apply(cap: CapMaterial, material: StandardMaterial) -> StandardMaterial {
+    if (cap.enabled == 0u) {
+        return material;
+    }
+    var out = material;
+    out.emissive = cap.emissive;
+    out.metallic = cap.metallic;
+    out.perceptual_roughness = cap.perceptual_roughness;
+    out.reflectance = vec3(cap.reflectance);
+    out.clearcoat = cap.clearcoat;
+    out.clearcoat_perceptual_roughness = cap.clearcoat_perceptual_roughness;
+    return out;
+}
+
+// Where the view ray through a fragment hits a cap in world space and its
+// depth.
+struct CapHit {
+    position: vec3<f32>,
+    depth: f32,
+    // False when the back face is not seen through the cut, e.g. an inner
+    // wall seen by a camera inside the mesh.
+    is_cap: bool,
+}
+
+// Return where the view ray through a fragment hits a cap's plane, which is
+// in the space it is tested in. A fragment whose ray does not hit the plane
+// between the camera and itself is not a cap and keeps its own position and
+// depth.
+fn plane_cap_hit(
+    frag_coord: vec4<f32>,
+    world_position: vec3<f32>,
+    depth: f32,
+    plane: vec4<f32>,
+    screen_space: bool,
+) -> CapHit {
+    var position: vec3<f32>;
+    var cap_depth: f32;
+    if (screen_space) {
+        if (abs(plane.z) < 1e-6) {
+            // The plane is parallel to the view ray.
+            return CapHit(world_position, depth, false);
+        }
+        cap_depth = (plane.w - dot(plane.xy, frag_coord.xy)) / plane.z;
+        position = position_ndc_to_world(frag_coord_to_ndc(vec4(frag_coord.xy, cap_depth, 1.0)));
+    } else {
+        position = plane_ray_hit(frag_coord.xy, plane);
+        cap_depth = position_world_to_ndc(position).z;
+    }
+#ifndef UNCLIPPED_DEPTH_ORTHO_EMULATION
+    if (cap_depth > 1.0) {
+        // Behind the camera.
+        return CapHit(world_position, depth, false);
+    }
+#endif
+    // Depth is reversed, so nearer is greater. NaN fails too.
+    if (cap_depth >= depth) {
+        return CapHit(position, cap_depth, true);
+    }
+    return CapHit(world_position, depth, false);
+}
+
+// Return whether a fragment faces the camera. A transform with a negative
+// scale mirrors the mesh and reverses its winding, which flips front_facing.
+fn is_facing(is_front: bool, instance_index: u32) -> bool {
+    return is_front == (sign_determinant_model_3x3m(mesh[instance_index].flags) > 0.0);
+}
+
+// Return where a world position that moves with the mesh in model space was
+// last frame.
+fn previous_position(position: vec3<f32>, instance_index: u32, model_space: bool) -> vec3<f32> {
+    if (model_space) {
+        let local_position = get_local_from_world(instance_index) * vec4(position, 1.0);
+        return (get_previous_world_from_local(instance_index) * local_position).xyz;
+    }
+    return position;
+}
+
+// Return the depth a fragment writes without a cap.
+fn fragment_depth(in: VertexOutput) -> f32 {
+#ifdef UNCLIPPED_DEPTH_ORTHO_EMULATION
+    return in.unclipped_depth;
+#else
+    return in.position.z;
+#endif
+}
+
+// The cap writes its own depth, so the outputs are Bevy's with a depth.
+#ifdef PREPASS_PIPELINE
+#ifdef PREPASS_FRAGMENT
+struct PlaneCutFragmentOutput {
+#ifdef NORMAL_PREPASS
+    @location(0) normal: vec4<f32>,
+#endif
+#ifdef MOTION_VECTOR_PREPASS
+    @location(1) motion_vector: vec2<f32>,
+#endif
+#ifdef DEFERRED_PREPASS
+    @location(2) deferred: vec4<u32>,
+    @location(3) deferred_lighting_pass_id: u32,
+#endif
+    @builtin(frag_depth) frag_depth: f32,
+}
+
+#ifdef DEFERRED_PREPASS
+// Add a depth to the deferred prepass's output.
+fn with_frag_depth(out: FragmentOutput, frag_depth: f32) -> PlaneCutFragmentOutput {
+    var depth_out: PlaneCutFragmentOutput;
+#ifdef NORMAL_PREPASS
+    depth_out.normal = out.normal;
+#endif
+#ifdef MOTION_VECTOR_PREPASS
+    depth_out.motion_vector = out.motion_vector;
+#endif
+    depth_out.deferred = out.deferred;
+    depth_out.deferred_lighting_pass_id = out.deferred_lighting_pass_id;
+    depth_out.frag_depth = frag_depth;
+    return depth_out;
+}
+#endif
+#endif
+#else
+struct PlaneCutFragmentOutput {
+    @location(0) color: vec4<f32>,
+    @builtin(frag_depth) frag_depth: f32,
+}
+#endif
+
+// Return where the view ray through a fragment hits the cap of the plane
+// `nplane_cut` picked. Only back faces can be caps.
+fn nplane_cut_cap_hit(in: VertexOutput, facing: bool, plane_index: i32) -> CapHit {
+    if (facing || plane_index < 0) {
+        return CapHit(in.world_position.xyz, fragment_depth(in), false);
+    }
+    return plane_cap_hit(
+        in.position,
+        in.world_position.xyz,
+        fragment_depth(in),
+        nplane_cut_plane(u32(plane_index), in.instance_index),
+        (plane_cut_ext.flags & PLANE_CUT_FLAGS_SCREENSPACE_BIT) != 0u,
+    );
+}
+
+// PLANE_CUT_PREPASS is set by the plugin for the depth, normal, and motion
+// vector prepass, which also draws shadows.
+#ifdef PLANE_CUT_PREPASS
+#ifdef PREPASS_FRAGMENT
+@fragment
+fn fragment(
+    in: VertexOutput,
+    @builtin(front_facing) is_front: bool,
+) -> PlaneCutFragmentOutput {
+    let facing = is_facing(is_front, in.instance_index);
+    let plane_index = nplane_cut(in.position, in.world_position, in.instance_index, facing);
+    prepass_alpha_discard(in);
+
+    var out: PlaneCutFragmentOutput;
+    let hit = nplane_cut_cap_hit(in, facing, plane_index);
+    if (!hit.is_cap && is_dithered_out(in.position, nplane_cut_soft_opacity(in))) {
+        discard;
+    }
+    out.frag_depth = hit.depth;
+#ifdef NORMAL_PREPASS
+    var normal = in.world_normal;
+    if (hit.is_cap) {
+        normal = -normalize(nplane_cut_plane(u32(plane_index), in.instance_index).xyz);
+    } else if (!facing) {
+        // An inner wall faces the camera inside the mesh.
+        normal = -normal;
+    }
+    out.normal = vec4(normal * 0.5 + vec3(0.5), 1.0);
+#endif
+#ifdef MOTION_VECTOR_PREPASS
+    var previous_world_position = in.previous_world_position;
+    if (hit.is_cap) {
+        let model_space = (plane_cut_ext.flags & PLANE_CUT_FLAGS_MODELSPACE_BIT) != 0u;
+        previous_world_position = vec4(previous_position(hit.position, in.instance_index, model_space), 1.0);
+    }
+    out.motion_vector = calculate_motion_vector(vec4(hit.position, 1.0), previous_world_position);
+#endif
+    return out;
+}
+#else
+// Depth only, e.g. shadows. The plane cut plugin adds this fragment stage
+// because without it the cut away portion would still write depth.
+@fragment
+fn fragment(
+    in: VertexOutput,
+    @builtin(front_facing) is_front: bool,
+) -> @builtin(frag_depth) f32 {
+    let facing = is_facing(is_front, in.instance_index);
+    let plane_index = nplane_cut(in.position, in.world_position, in.instance_index, facing);
+    prepass_alpha_discard(in);
+    let hit = nplane_cut_cap_hit(in, facing, plane_index);
+    if (!hit.is_cap && is_dithered_out(in.position, nplane_cut_soft_opacity(in))) {
+        discard;
+    }
+    return hit.depth;
+}
+#endif
+#else
+@fragment
+fn fragment(
+    in_: VertexOutput,
+    @builtin(front_facing) is_front: bool,
+) -> PlaneCutFragmentOutput {
+    var in = in_;
+
+    let shaded = (plane_cut_ext.flags & PLANE_CUT_FLAGS_SHADED_BIT) != 0u;
+    let facing = is_facing(is_front, in.instance_index);
+    let plane_index = nplane_cut(in.position, in.world_position, in.instance_index, facing);
+    // The edge is measured from where the surface is.
+    let edge_distance = nplane_cut_distance(in.position, in.world_position, in.instance_index);
+
+    let hit = nplane_cut_cap_hit(in, facing, plane_index);
+    let is_cap = hit.is_cap;
+    // A back face that is not a cap is an inner wall seen from inside the mesh.
+    let is_wall = !is_cap && !facing;
+    if (is_cap) {
+        // Light the cap where it is on its plane rather than at the back face.
+        in.world_position = vec4(hit.position, 1.0);
+#ifdef PREPASS_PIPELINE
+#ifdef MOTION_VECTOR_PREPASS
+        let model_space = (plane_cut_ext.flags & PLANE_CUT_FLAGS_MODELSPACE_BIT) != 0u;
+        in.previous_world_position = vec4(previous_position(hit.position, in.instance_index, model_space), 1.0);
+#endif
+#endif
+    }
+    if (is_cap && shaded) {
+        in.world_normal = -nplane_cut_plane(u32(plane_index), in.instance_index).xyz;
+    }
+    // Generate a PbrInput struct from the StandardMaterial bindings. Only a
+    // wall's normal is flipped to face the camera, and only if double sided.
+    var pbr_input = pbr_input_from_standard_material(in, !is_wall);
+    if (is_wall && (pbr_input.material.flags & STANDARD_MATERIAL_FLAGS_DOUBLE_SIDED_BIT) == 0u) {
+        pbr_input.world_normal = -pbr_input.world_normal;
+        pbr_input.N = -pbr_input.N;
+        pbr_input.clearcoat_N = -pbr_input.clearcoat_N;
+    }
+    in.world_normal = pbr_input.world_normal;
+
+    if (is_cap && shaded) {
+        pbr_input.material.base_color = nplane_cut_cap_color(u32(plane_index), in.position, in.instance_index);
+        pbr_input.material = cap_material_apply(
+            nplane_cut_cap_material(u32(plane_index)),
+            pbr_input.material,
+        );
+        // A cap is not transmissive. It blends by its color's alpha instead.
+        pbr_input.material.specular_transmission = 0.0;
+        pbr_input.material.diffuse_transmission = 0.0;
+    }
+    if (!is_cap) {
+        // Emissive survives into the deferred lighting pass.
+        pbr_input.material.emissive += vec4(cut_edge_glow(plane_cut_ext.edge, edge_distance), 0.0);
+        // Fade out toward the plane of a soft cut.
+        let opacity = soft_cut_opacity(plane_cut_ext.soft_width, edge_distance);
+        if (is_alpha_blended(pbr_input.material)) {
+            pbr_input.material.base_color.a *= opacity;
+        } else if (is_dithered_out(in.position, opacity)) {
+            discard;
+        }
     }
 
     // Alpha discard
     pbr_input.material.base_color = alpha_discard(pbr_input.material, pbr_input.material.base_color);
 
-    // Object space
-    // XXX: Might have to do object space in the vertex shader.
-
 #ifdef PREPASS_PIPELINE
     // In deferred mode we can't modify anything after that, as lighting is run
     // in a separate fullscreen shader.
-    let out = deferred_output(in, pbr_input);
+    let out = with_frag_depth(deferred_output(in, pbr_input), hit.depth);
 #else
-    var out: FragmentOutput;
+    var out: PlaneCutFragmentOutput;
+    out.frag_depth = hit.depth;
     // Apply lighting
     out.color = apply_pbr_lighting(pbr_input);
 
     out.color = main_pass_post_lighting_processing(pbr_input, out.color);
-    if (!shaded && !is_front && plane_index >= 0) {
-        out.color = plane_cut_ext.color[plane_index];
+    if (!shaded && is_cap) {
+        out.color = nplane_cut_cap_color(u32(plane_index), in.position, in.instance_index);
+    }
+#ifdef OIT_ENABLED
+    let alpha_mode = pbr_input.material.flags & STANDARD_MATERIAL_FLAGS_ALPHA_MODE_RESERVED_BITS;
+    if (alpha_mode != STANDARD_MATERIAL_FLAGS_ALPHA_MODE_OPAQUE) {
+        // Sort the cap by its own depth in the resolve pass.
+        oit_draw(vec4(in.position.xy, hit.depth, in.position.w), out.color);
+        discard;
     }
+#endif
 #endif
 
     return out;
 }
+#endif
@@ -0,0 +1,636 @@
+use bevy::{
+    app::{App, Plugin, PreUpdate},
+    asset::Assets,
+    ecs::{
+        entity::Entity,
+        event::EventWriter,
+        query::{Has, Or, QueryItem, With},
+        reflect::ReflectResource,
+        resource::Resource,
+        schedule::IntoScheduleConfigs,
+        system::{Query, Res, ResMut, SystemParam},
+    },
+    math::{bounding::Aabb3d, Ray3d, Vec2, Vec3, Vec3Swizzles, Vec4, Vec4Swizzles},
+    pbr::MeshMaterial3d,
+    picking::{
+        backend::{ray::RayMap, HitData, PointerHits},
+        mesh_picking::{
+            ray_cast::{
+                ray_aabb_intersection_3d, MeshRayCast, MeshRayCastSettings, RayCastVisibility,
+            },
+            MeshPickingCamera, MeshPickingSettings,
+        },
+        pointer::PointerId,
+        PickSet, Pickable,
+    },
+    platform::collections::HashMap,
+    reflect::{std_traits::ReflectDefault, Reflect},
+    render::{
+        camera::Camera,
+        mesh::{Mesh, Mesh3d, PrimitiveTopology},
+        primitives::Aabb,
+        renderer::RenderDevice,
+        view::{InheritedVisibility, RenderLayers, ViewVisibility},
+    },
+    time::Time,
+    transform::components::GlobalTransform,
+};
+use super::*;
+use crate::{
+    clip::frag_coord,
+    nplane::{max_planes, Combine, NPlaneCutExt, NPlaneCutMaterial},
+};
+
+/// Pick meshes with cut materials where they are drawn.
+///
+/// Bevy's `MeshPickingPlugin` hits the triangles of a [`PlaneCutMaterial`] or
+/// [`NPlaneCutMaterial`] that the shader cuts away, so clicking a cap selects
+/// the hidden surface in front of it. Add this plugin instead of
+/// `MeshPickingPlugin`. It picks other meshes like `MeshPickingPlugin` does
+/// with the same `MeshPickingSettings`. A cut mesh is hit on the nearest
+/// triangle the shader keeps or on the cap in front of it, which
+/// [`PlaneCutCapHits`] records. Soft cuts are picked as if they were hard.
+pub struct PlaneCutPickingPlugin;
+
+impl Plugin for PlaneCutPickingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MeshPickingSettings>()
+            .register_type::<MeshPickingSettings>()
+            .init_resource::<PlaneCutPickingSettings>()
+            .register_type::<PlaneCutPickingSettings>()
+            .init_resource::<PlaneCutCapHits>()
+            .add_systems(PreUpdate, update_hits.in_set(PickSet::Backend));
+    }
+}
+
+/// Runtime settings for the [`PlaneCutPickingPlugin`].
+#[derive(Resource, Reflect, Debug, Clone)]
+#[reflect(Resource, Default)]
+pub struct PlaneCutPickingSettings {
+    /// Hit the caps of cut meshes. If false, a ray through a cap does not hit
+    /// its mesh. True is the default.
+    pub caps: bool,
+}
+
+impl Default for PlaneCutPickingSettings {
+    fn default() -> Self {
+        Self { caps: true }
+    }
+}
+
+/// Where a pointer hits a cap.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CapHit {
+    /// The index of the cap's plane. It is zero for a [`PlaneCutMaterial`].
+    pub plane_index: usize,
+    /// The world position of the hit.
+    pub position: Vec3,
+}
+
+/// The caps each pointer hits as of the last picking backend update.
+///
+/// The `HitData` of a pointer event does not say whether it is on a cap, so
+/// look it up here, e.g., in an observer of `Pointer<Click>`.
+#[derive(Resource, Default, Debug)]
+pub struct PlaneCutCapHits(HashMap<(PointerId, Entity), CapHit>);
+
+impl PlaneCutCapHits {
+    /// Return the cap a pointer hits on an entity.
+    pub fn get(&self, pointer: PointerId, entity: Entity) -> Option<&CapHit> {
+        self.0.get(&(pointer, entity))
+    }
+
+    /// Iterate over the pointers, the entities, and the caps they hit.
+    pub fn iter(&self) -> impl Iterator<Item = (PointerId, Entity, &CapHit)> {
+        self.0
+            .iter()
+            .map(|((pointer, entity), hit)| (*pointer, *entity, hit))
+    }
+}
+
+/// Which entities may be picked, like `MeshPickingPlugin`'s filter.
+#[derive(SystemParam)]
+struct Pickables<'w, 's> {
+    settings: Res<'w, MeshPickingSettings>,
+    targets: Query<'w, 's, (Option<&'static Pickable>, Option<&'static RenderLayers>)>,
+}
+
+impl Pickables<'_, '_> {
+    fn can_pick(&self, entity: Entity, camera_layers: &RenderLayers) -> bool {
+        let (pickable, layers) = self.targets.get(entity).unwrap_or_default();
+        (!self.settings.require_markers || pickable.is_some())
+            && camera_layers.intersects(layers.unwrap_or(&RenderLayers::default()))
+            && pickable.is_none_or(|pickable| pickable.is_hoverable)
+    }
+
+    fn blocks(&self, entity: Entity) -> bool {
+        self.targets
+            .get(entity)
+            .is_ok_and(|(pickable, _)| pickable.is_some_and(|pickable| pickable.should_block_lower))
+    }
+}
+
+type CutMeshData = (
+    Entity,
+    &'static Mesh3d,
+    &'static GlobalTransform,
+    Option<&'static MeshMaterial3d<PlaneCutMaterial>>,
+    Option<&'static MeshMaterial3d<NPlaneCutMaterial>>,
+    Option<&'static Aabb>,
+    &'static InheritedVisibility,
+    &'static ViewVisibility,
+);
+
+type CutMeshFilter = Or<(
+    With<MeshMaterial3d<PlaneCutMaterial>>,
+    With<MeshMaterial3d<NPlaneCutMaterial>>,
+)>;
+
+/// The meshes with cut materials.
+#[derive(SystemParam)]
+struct CutMeshes<'w, 's> {
+    query: Query<'w, 's, CutMeshData, CutMeshFilter>,
+    meshes: Res<'w, Assets<Mesh>>,
+    materials: Res<'w, Assets<PlaneCutMaterial>>,
+    nplane_materials: Res<'w, Assets<NPlaneCutMaterial>>,
+    settings: Res<'w, PlaneCutPickingSettings>,
+    time: Res<'w, Time>,
+    render_device: Option<Res<'w, RenderDevice>>,
+}
+
+/// A camera as the shaders see it.
+struct View<'a> {
+    camera: &'a Camera,
+    transform: &'a GlobalTransform,
+}
+
+impl View<'_> {
+    /// Return the frag coord of a world position: its pixel and its depth.
+    fn frag_coord(&self, position: Vec3) -> Option<Vec3> {
+        frag_coord(self.camera, self.transform, position)
+    }
+
+    /// Return the world position of a frag coord.
+    fn world_position(&self, frag_coord: Vec3) -> Option<Vec3> {
+        let rect = self.camera.physical_viewport_rect()?;
+        let (min, max) = (rect.min.as_vec2(), rect.max.as_vec2());
+        let uv = (frag_coord.xy() - min) / (max - min);
+        self.camera.ndc_to_world(
+            self.transform,
+            Vec3::new(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, frag_coord.z),
+        )
+    }
+}
+
+/// A point of a mesh that the ray hits as the shaders see it.
+struct Fragment {
+    point: CutPoint,
+    facing: bool,
+}
+
+/// What a cut shader does with a fragment.
+enum Shaded {
+    Cut,
+    Surface,
+    /// The index of the plane and where and with what normal its cap is hit.
+    Cap(usize, Vec3, Vec3),
+}
+
+/// A triangle the ray hits.
+struct TriangleHit {
+    distance: f32,
+    position: Vec3,
+    /// The unnormalized normal of the triangle's winding in world space.
+    normal: Vec3,
+}
+
+/// Return where a ray hits a triangle from either side.
+fn ray_triangle(ray: Ray3d, a: Vec3, b: Vec3, c: Vec3) -> Option<TriangleHit> {
+    let ab = b - a;
+    let ac = c - a;
+    let p = ray.direction.cross(ac);
+    let determinant = ab.dot(p);
+    if determinant.abs() < f32::EPSILON {
+        return None;
+    }
+    let s = ray.origin - a;
+    let u = s.dot(p) / determinant;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = s.cross(ab);
+    let v = ray.direction.dot(q) / determinant;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    let distance = ac.dot(q) / determinant;
+    (distance >= 0.0).then(|| TriangleHit {
+        distance,
+        position: ray.get_point(distance),
+        normal: ab.cross(ac),
+    })
+}
+
+/// Return every triangle of a mesh a ray hits, nearest first.
+fn triangle_hits(mesh: &Mesh, transform: &GlobalTransform, ray: Ray3d) -> Vec<TriangleHit> {
+    let mut hits = Vec::new();
+    if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+        return hits;
+    }
+    let Some(positions) = mesh
+        .attribute(Mesh::ATTRIBUTE_POSITION)
+        .and_then(|positions| positions.as_float3())
+    else {
+        return hits;
+    };
+    let affine = transform.affine();
+    let positions: Vec<Vec3> = positions
+        .iter()
+        .map(|position| affine.transform_point3((*position).into()))
+        .collect();
+    let mut hit = |a: usize, b: usize, c: usize| {
+        if let (Some(a), Some(b), Some(c)) = (positions.get(a), positions.get(b), positions.get(c))
+        {
+            hits.extend(ray_triangle(ray, *a, *b, *c));
+        }
+    };
+    match mesh.indices() {
+        Some(indices) => {
+            let indices: Vec<usize> = indices.iter().collect();
+            for triangle in indices.chunks_exact(3) {
+                hit(triangle[0], triangle[1], triangle[2]);
+            }
+        }
+        None => {
+            for i in (0..positions.len() / 3).map(|i| i * 3) {
+                hit(i, i + 1, i + 2);
+            }
+        }
+    }
+    hits.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+    hits
+}
+
+/// Return where the ray hits a cap's plane, which is in the space it is
+/// tested in, if it does between the camera and a back face like the
+/// shader's `plane_cap_hit`, and the world normal of the cap there, which
+/// faces the ray.
+fn cap_hit(
+    plane: Vec4,
+    screen: bool,
+    fragment: &Fragment,
+    view: &View,
+    ray: Ray3d,
+) -> Option<(Vec3, Vec3)> {
+    let (position, depth, normal) = if screen {
+        if plane.z.abs() < 1e-6 {
+            // The plane is parallel to the view ray.
+            return None;
+        }
+        let depth_at = |pixel: Vec2| (plane.w - plane.xy().dot(pixel)) / plane.z;
+        let world_at = |pixel: Vec2| view.world_position(pixel.extend(depth_at(pixel)));
+        let pixel = fragment.point.frag_coord.xy();
+        let position = world_at(pixel)?;
+        // The plane is in frag coords, so find its world normal from the
+        // points a pixel over and a pixel down.
+        let normal = (world_at(pixel + Vec2::X)? - position)
+            .cross(world_at(pixel + Vec2::Y)? - position)
+            .try_normalize()
+            .unwrap_or(-*ray.direction);
+        (position, depth_at(pixel), normal)
+    } else {
+        let position = ray
+            .get_point((plane.w - plane.xyz().dot(ray.origin)) / plane.xyz().dot(*ray.direction));
+        (position, view.frag_coord(position)?.z, -plane.xyz().normalize())
+    };
+    let normal = if normal.dot(*ray.direction) > 0.0 {
+        -normal
+    } else {
+        normal
+    };
+    // Depth is reversed, so nearer is greater. NaN fails too.
+    (depth <= 1.0 && depth >= fragment.point.frag_coord.z).then_some((position, normal))
+}
+
+/// Shade a fragment like `plane_cut.wgsl`.
+fn plane_cut_fragment(cut: &PlaneCutExt, fragment: &Fragment, view: &View, ray: Ray3d) -> Shaded {
+    if cut.is_clipped(fragment.point) {
+        return Shaded::Cut;
+    }
+    if fragment.facing {
+        return Shaded::Surface;
+    }
+    let plane = cut.tested_plane(&fragment.point.transform, fragment.point.time);
+    cap_hit(plane, matches!(cut.space, Space::Screen), fragment, view, ray)
+        .map_or(Shaded::Surface, |(cap, normal)| Shaded::Cap(0, cap, normal))
+}
+
+/// Return where a line meets a plane like the shader's
+/// `intersect_plane_line`. It is NaN if they are parallel.
+fn intersect_plane_line(plane: Vec4, v: Vec3, r: Vec3) -> f32 {
+    let denominator = plane.xyz().dot(v);
+    if denominator.abs() < 1e-6 {
+        return f32::NAN;
+    }
+    (plane.w - plane.xyz().dot(r)) / denominator
+}
+
+/// Return `None` if a fragment is cut by the intersection of planes or the
+/// index of the plane whose cap a back face shows like the shader's
+/// `nplane_cut`.
+fn nplane_cut_intersection(
+    planes: &[Vec4],
+    position: Vec3,
+    view_ray: Vec3,
+    view_position: Vec3,
+    facing: bool,
+) -> Option<Option<usize>> {
+    let mut plane_index = None;
+    let mut t_max = 0.0;
+    let mut back_discard = false;
+    for (i, plane) in planes.iter().enumerate() {
+        if position.dot(plane.xyz()) - plane.w < 0.0 {
+            if facing {
+                return None;
+            }
+            back_discard = true;
+        }
+        let t = intersect_plane_line(*plane, view_ray, view_position);
+        if t.is_nan() || t < 0.0 {
+            continue;
+        }
+        if view_ray.dot(plane.xyz()) > 0.0 && t > t_max {
+            plane_index = Some(i);
+            t_max = t;
+        }
+    }
+    if back_discard {
+        let mut hits = 0;
+        let mut t_front = -3e10;
+        let mut t_back = 3e10;
+        for plane in planes {
+            let t = intersect_plane_line(*plane, -view_ray, position);
+            if t.is_nan() || t < 0.0 {
+                continue;
+            }
+            hits += 1;
+            if view_ray.dot(plane.xyz()) > 0.0 {
+                t_front = t.max(t_front);
+            } else {
+                t_back = t.min(t_back);
+            }
+        }
+        if hits == 0 || t_front < t_back {
+            return None;
+        }
+    }
+    Some(plane_index)
+}
+
+/// Return `None` if a fragment is cut by the union of planes or the index of
+/// the plane whose cap a back face shows like the shader's
+/// `nplane_cut_union`. The view ray is in the space of the planes.
+fn nplane_cut_union(
+    planes: &[Vec4],
+    position: Vec3,
+    view_ray: Vec3,
+    view_position: Vec3,
+    facing: bool,
+) -> Option<Option<usize>> {
+    if !planes
+        .iter()
+        .any(|plane| position.dot(plane.xyz()) - plane.w >= 0.0)
+    {
+        return None;
+    }
+    if facing {
+        return Some(None);
+    }
+    let mut plane_index = None;
+    let mut t_enter: f32 = 0.0;
+    let mut t_exit = 3e10;
+    for (i, plane) in planes.iter().enumerate() {
+        let denominator = plane.xyz().dot(view_ray);
+        let eye_distance = plane.xyz().dot(view_position) - plane.w;
+        if denominator.abs() < 1e-6 {
+            if eye_distance >= 0.0 {
+                return Some(None);
+            }
+            continue;
+        }
+        let t = -eye_distance / denominator;
+        if denominator > 0.0 {
+            if t < t_exit {
+                t_exit = t;
+                plane_index = Some(i);
+            }
+        } else {
+            t_enter = t_enter.max(t);
+        }
+    }
+    if t_enter >= t_exit {
+        return Some(None);
+    }
+    Some(plane_index)
+}
+
+/// Shade a fragment like `nplane_cut.wgsl`.
+fn nplane_cut_fragment(cut: &NPlaneCutExt, fragment: &Fragment, view: &View, ray: Ray3d) -> Shaded {
+    let screen = matches!(cut.space, Space::Screen);
+    let planes: Vec<Vec4> = cut
+        .tested_planes(&fragment.point.transform, fragment.point.max_planes)
+        .collect();
+    let position = if screen {
+        fragment.point.frag_coord
+    } else {
+        fragment.point.position
+    };
+    let view_position = view.transform.translation();
+    let view_ray = (fragment.point.position - view_position).normalize();
+    let plane_index = match cut.combine {
+        Combine::Intersection => {
+            nplane_cut_intersection(&planes, position, view_ray, view_position, fragment.facing)
+        }
+        // In frag coords the view ray runs from the near plane, which is at
+        // depth one, along the pixel to smaller depths.
+        Combine::Union if screen => nplane_cut_union(
+            &planes,
+            position,
+            Vec3::NEG_Z,
+            fragment.point.frag_coord.xy().extend(1.0),
+            fragment.facing,
+        ),
+        Combine::Union => {
+            nplane_cut_union(&planes, position, view_ray, view_position, fragment.facing)
+        }
+    };
+    let Some(plane_index) = plane_index else {
+        return Shaded::Cut;
+    };
+    match plane_index {
+        Some(i) if !fragment.facing => cap_hit(planes[i], screen, fragment, view, ray)
+            .map_or(Shaded::Surface, |(cap, normal)| Shaded::Cap(i, cap, normal)),
+        _ => Shaded::Surface,
+    }
+}
+
+impl CutMeshes<'_, '_> {
+    /// Return where a ray hits a cut mesh as it is drawn and the cap it hits,
+    /// if any.
+    fn cast(
+        &self,
+        (_, mesh, transform, material, nplane_material, _, _, _): &QueryItem<'_, CutMeshData>,
+        view: &View,
+        camera: Entity,
+        ray: Ray3d,
+    ) -> Option<(HitData, Option<CapHit>)> {
+        let mesh = self.meshes.get(*mesh)?;
+        let cut = material.and_then(|material| self.materials.get(material));
+        let nplane_cut = nplane_material.and_then(|material| self.nplane_materials.get(material));
+        let faces = match (cut, nplane_cut) {
+            (Some(cut), _) => cut.extension.faces,
+            (_, Some(cut)) => cut.extension.faces,
+            _ => return None,
+        };
+        let time = self.time.elapsed_secs_wrapped();
+        let max_planes = self.render_device.as_deref().and_then(max_planes);
+        let is_mirrored = transform.affine().matrix3.determinant() < 0.0;
+        for hit in triangle_hits(mesh, transform, ray) {
+            let is_front = hit.normal.dot(*ray.direction) < 0.0;
+            let culled = match faces.cull_mode() {
+                Some(Face::Front) => is_front,
+                Some(Face::Back) => !is_front,
+                None => false,
+            };
+            if culled {
+                continue;
+            }
+            let Some(frag_coord) = view.frag_coord(hit.position) else {
+                continue;
+            };
+            let fragment = Fragment {
+                point: CutPoint {
+                    position: hit.position,
+                    transform: **transform,
+                    frag_coord,
+                    time,
+                    max_planes,
+                },
+                // A mirrored mesh's winding is reversed.
+                facing: is_front != is_mirrored,
+            };
+            let shaded = match (cut, nplane_cut) {
+                (Some(cut), _) => plane_cut_fragment(&cut.extension, &fragment, view, ray),
+                (_, Some(cut)) => nplane_cut_fragment(&cut.extension, &fragment, view, ray),
+                _ => return None,
+            };
+            match shaded {
+                Shaded::Cut => continue,
+                Shaded::Surface => {
+                    let normal = hit.normal.normalize() * if is_mirrored { -1.0 } else { 1.0 };
+                    return Some((
+                        HitData::new(camera, hit.distance, Some(hit.position), Some(normal)),
+                        None,
+                    ));
+                }
+                Shaded::Cap(plane_index, position, normal) => {
+                    // The cap hides the rest of the mesh behind it.
+                    if !self.settings.caps {
+                        return None;
+                    }
+                    let distance = (position - ray.origin).dot(*ray.direction);
+                    return Some((
+                        HitData::new(camera, distance, Some(position), Some(normal)),
+                        Some(CapHit {
+                            plane_index,
+                            position,
+                        }),
+                    ));
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Casts rays into the scene like `MeshPickingPlugin` and sends
+/// [`PointerHits`] events, picking cut meshes where they are drawn.
+fn update_hits(
+    pickables: Pickables,
+    ray_map: Res<RayMap>,
+    cameras: Query<(
+        &Camera,
+        &GlobalTransform,
+        Has<MeshPickingCamera>,
+        Option<&RenderLayers>,
+    )>,
+    cut_meshes: CutMeshes,
+    mut ray_cast: MeshRayCast,
+    mut cap_hits: ResMut<PlaneCutCapHits>,
+    mut output: EventWriter<PointerHits>,
+) {
+    cap_hits.0.clear();
+    let visibility = pickables.settings.ray_cast_visibility;
+    for (&ray_id, &ray) in ray_map.iter() {
+        let Ok((camera, camera_transform, can_pick, camera_layers)) = cameras.get(ray_id.camera)
+        else {
+            continue;
+        };
+        if pickables.settings.require_markers && !can_pick {
+            continue;
+        }
+        let camera_layers = camera_layers.cloned().unwrap_or_default();
+        let settings = MeshRayCastSettings {
+            visibility,
+            filter: &|entity| {
+                !cut_meshes.query.contains(entity) && pickables.can_pick(entity, &camera_layers)
+            },
+            early_exit_test: &|entity| pickables.blocks(entity),
+        };
+        let mut picks: Vec<(Entity, HitData)> = ray_cast
+            .cast_ray(ray, &settings)
+            .iter()
+            .map(|(entity, hit)| {
+                (
+                    *entity,
+                    HitData::new(
+                        ray_id.camera,
+                        hit.distance,
+                        Some(hit.point),
+                        Some(hit.normal),
+                    ),
+                )
+            })
+            .collect();
+        let view = View {
+            camera,
+            transform: camera_transform,
+        };
+        for item in &cut_meshes.query {
+            let (entity, _, transform, _, _, aabb, inherited_visibility, view_visibility) = item;
+            let visible = match visibility {
+                RayCastVisibility::Any => true,
+                RayCastVisibility::Visible => inherited_visibility.get(),
+                RayCastVisibility::VisibleInView => view_visibility.get(),
+            };
+            if !visible || !pickables.can_pick(entity, &camera_layers) {
+                continue;
+            }
+            if let Some(aabb) = aabb {
+                let aabb = Aabb3d::new(aabb.center, aabb.half_extents);
+                if ray_aabb_intersection_3d(ray, &aabb, &transform.compute_matrix()).is_none() {
+                    continue;
+                }
+            }
+            if let Some((hit, cap)) = cut_meshes.cast(&item, &view, ray_id.camera, ray) {
+                if let Some(cap) = cap {
+                    cap_hits.0.insert((ray_id.pointer, entity), cap);
+                }
+                picks.push((entity, hit));
+            }
+        }
+        if !picks.is_empty() {
+            output.write(PointerHits::new(ray_id.pointer, picks, camera.order as f32));
+        }
+    }
+}
@@ -1,6 +1,17 @@
 #import bevy_pbr::{
     pbr_fragment::pbr_input_from_standard_material,
+    pbr_types::{
+        StandardMaterial, STANDARD_MATERIAL_FLAGS_DOUBLE_SIDED_BIT,
+        STANDARD_MATERIAL_FLAGS_ALPHA_MODE_RESERVED_BITS, STANDARD_MATERIAL_FLAGS_ALPHA_MODE_OPAQUE,
+        STANDARD_MATERIAL_FLAGS_ALPHA_MODE_MASK,
+    },
     pbr_functions::alpha_discard,
+    mesh_functions::{
+        get_world_from_local, get_local_from_world, get_previous_world_from_local,
+        mesh_normal_local_to_world, sign_determinant_model_3x3m,
+    },
+    mesh_bindings::mesh,
+    view_transformations::{frag_coord_to_ndc, position_ndc_to_world, position_world_to_ndc},
         // mesh::vertex,
 }
 
@@ -8,81 +19,706 @@
 #import bevy_pbr::{
     prepass_io::{VertexOutput, FragmentOutput},
     pbr_deferred_functions::deferred_output,
+    pbr_prepass_functions::{prepass_alpha_discard, calculate_motion_vector},
 }
 #else
 #import bevy_pbr::{
     forward_io::{VertexOutput, FragmentOutput},
     pbr_functions::{apply_pbr_lighting, main_pass_post_lighting_processing},
 }
+#ifdef OIT_ENABLED
+#import bevy_core_pipeline::oit::oit_draw
 #endif
+#endif
+
+#ifdef PREPASS_PIPELINE
+#import bevy_render::globals::Globals
+// The prepass binds the globals in its own place.
+@group(0) @binding(1) var<uniform> globals: Globals;
+#else
+#import bevy_pbr::mesh_view_bindings::globals
+#endif
+
+struct CutEdge {
+    color: vec4<f32>,
+    width: f32,
+    falloff: f32,
+}
+
+struct Dissolve {
+    edge_color: vec4<f32>,
+    scale: f32,
+    amplitude: f32,
+    speed: f32,
+    edge_width: f32,
+    start: f32,
+}
+
+struct CapMaterial {
+    emissive: vec4<f32>,
+    metallic: f32,
+    perceptual_roughness: f32,
+    reflectance: f32,
+    clearcoat: f32,
+    clearcoat_perceptual_roughness: f32,
+    enabled: u32,
+}
+
+struct CapPattern {
+    color: vec4<f32>,
+    angle: f32,
+    spacing: f32,
+    line_width: f32,
+    hatch: u32,
+}
+const CAP_PATTERN_SOLID: u32 = 0u;
+const CAP_PATTERN_LINES: u32 = 1u;
+const CAP_PATTERN_CROSSHATCH: u32 = 2u;
+const CAP_PATTERN_DOTS: u32 = 3u;
+
+struct CapUv {
+    tiling: vec2<f32>,
+    offset: vec2<f32>,
+    rotation: f32,
+}
 
 struct PlaneCutExt {
     plane: vec4<f32>,
     color: vec4<f32>,
-    flags: u32
+    flags: u32,
+    edge: CutEdge,
+    soft_width: f32,
+    dissolve: Dissolve,
+    pattern: CapPattern,
+    cap_uv: CapUv,
+    cap_material: CapMaterial,
 }
 const PLANE_CUT_FLAGS_SCREENSPACE_BIT: u32 = 1u;
+const PLANE_CUT_FLAGS_MODELSPACE_BIT: u32 = 2u;
 const PLANE_CUT_FLAGS_SHADED_BIT: u32 = 4u;
+const PLANE_CUT_FLAGS_CAP_TEXTURE_BIT: u32 = 8u;
+const PLANE_CUT_FLAGS_CAP_NORMAL_MAP_BIT: u32 = 16u;
 
 @group(2) @binding(100)
 var<uniform> plane_cut_ext: PlaneCutExt;
+@group(2) @binding(109)
+var plane_cut_cap_texture: texture_2d<f32>;
+@group(2) @binding(110)
+var plane_cut_cap_sampler: sampler;
+@group(2) @binding(111)
+var plane_cut_cap_normal_map: texture_2d<f32>;
+@group(2) @binding(112)
+var plane_cut_cap_normal_sampler: sampler;
 
 // @vertex
 // fn my_vertex(vertex_no_morph: Vertex) -> VertexOutput {
 //     return vertex(vertex_no_morph);
 // }
 
+// Convert a plane in the mesh's local space into a plane in world space.
+fn plane_local_to_world(plane: vec4<f32>, instance_index: u32) -> vec4<f32> {
+    let point = plane.xyz * plane.w / dot(plane.xyz, plane.xyz);
+    let world_point = (get_world_from_local(instance_index) * vec4(point, 1.0)).xyz;
+    let world_normal = mesh_normal_local_to_world(plane.xyz, instance_index);
+    return vec4(world_normal, dot(world_normal, world_point));
+}
+
+// Return how long a dissolve's plane has moved. The clock wraps every hour
+// by default.
+fn dissolve_elapsed(dissolve: Dissolve) -> f32 {
+    let elapsed = globals.time - dissolve.start;
+    return select(elapsed, elapsed + 3600.0, elapsed < 0.0);
+}
+
+// Return the plane in the space it is tested in.
+fn plane_cut_plane(instance_index: u32) -> vec4<f32> {
+    var plane = plane_cut_ext.plane;
+    if ((plane_cut_ext.flags & PLANE_CUT_FLAGS_MODELSPACE_BIT) != 0u) {
+        // Object space: move the plane into world space with the mesh.
+        plane = plane_local_to_world(plane, instance_index);
+    }
+    // A dissolve moves the plane along its normal by the clock.
+    let dissolve = plane_cut_ext.dissolve;
+    plane.w += dissolve.speed * dissolve_elapsed(dissolve) * length(plane.xyz);
+    return plane;
+}
+
+// Is this fragment on the cut side of the plane?
+fn plane_cut_is_cut(
+    frag_coord: vec4<f32>,
+    world_position: vec4<f32>,
+    plane: vec4<f32>,
+    instance_index: u32,
+) -> bool {
+    let depth = plane_cut_dissolve_depth(frag_coord, world_position, instance_index) * length(plane.xyz);
+    if ((plane_cut_ext.flags & PLANE_CUT_FLAGS_SCREENSPACE_BIT) != 0u) {
+        // Screenspace
+        return dot(frag_coord.xyz, plane.xyz) < plane.w + depth;
+    } else {
+        // World or object space
+        return dot(world_position.xyz, plane.xyz) < plane.w + depth;
+    }
+}
+
+// Return a pseudorandom number in [0, 1) for a lattice point.
+fn hash_3d(p: vec3<f32>) -> f32 {
+    var q = fract(p * 0.1031);
+    q += dot(q, q.zyx + 31.32);
+    return fract((q.x + q.y) * q.z);
+}
+
+// Return smooth 3D value noise in [0, 1].
+fn value_noise_3d(p: vec3<f32>) -> f32 {
+    let i = floor(p);
+    let f = fract(p);
+    let u = f * f * (3.0 - 2.0 * f);
+    let x00 = mix(hash_3d(i), hash_3d(i + vec3(1.0, 0.0, 0.0)), u.x);
+    let x10 = mix(hash_3d(i + vec3(0.0, 1.0, 0.0)), hash_3d(i + vec3(1.0, 1.0, 0.0)), u.x);
+    let x01 = mix(hash_3d(i + vec3(0.0, 0.0, 1.0)), hash_3d(i + vec3(1.0, 0.0, 1.0)), u.x);
+    let x11 = mix(hash_3d(i + vec3(0.0, 1.0, 1.0)), hash_3d(i + vec3(1.0, 1.0, 1.0)), u.x);
+    return mix(mix(x00, x10, u.y), mix(x01, x11, u.y), u.z);
+}
+
+// Return three octaves of value noise in [0, 1].
+fn dissolve_noise(p: vec3<f32>) -> f32 {
+    return (value_noise_3d(p) + 0.5 * value_noise_3d(p * 2.0) + 0.25 * value_noise_3d(p * 4.0)) / 1.75;
+}
+
+// Return how far a dissolve's noise pushes the cut past the plane at a
+// fragment. The noise sticks to the mesh in model space and to the screen in
+// screen space.
+fn plane_cut_dissolve_depth(frag_coord: vec4<f32>, world_position: vec4<f32>, instance_index: u32) -> f32 {
+    let dissolve = plane_cut_ext.dissolve;
+    if (dissolve.amplitude <= 0.0) {
+        return 0.0;
+    }
+    var position = world_position.xyz;
+    if ((plane_cut_ext.flags & PLANE_CUT_FLAGS_SCREENSPACE_BIT) != 0u) {
+        position = vec3(frag_coord.xy, 0.0);
+    } else if ((plane_cut_ext.flags & PLANE_CUT_FLAGS_MODELSPACE_BIT) != 0u) {
+        position = (get_local_from_world(instance_index) * vec4(world_position.xyz, 1.0)).xyz;
+    }
+    return dissolve.amplitude * dissolve_noise(position * dissolve.scale);
+}
+
+// Return the glow of the edge band at a distance from the plane.
+fn cut_edge_glow(edge: CutEdge, distance: f32) -> vec3<f32> {
+    if (distance < 0.0 || distance >= edge.width) {
+        return vec3(0.0);
+    }
+    return edge.color.rgb * pow(1.0 - distance / edge.width, edge.falloff);
+}
+
+// Return how far a kept position is from the cut in the units it is tested
+// in.
+fn plane_cut_distance(
+    frag_coord: vec4<f32>,
+    world_position: vec4<f32>,
+    plane: vec4<f32>,
+    instance_index: u32,
+) -> f32 {
+    var position = world_position.xyz;
+    if ((plane_cut_ext.flags & PLANE_CUT_FLAGS_SCREENSPACE_BIT) != 0u) {
+        position = frag_coord.xyz;
+    }
+    return (dot(position, plane.xyz) - plane.w) / length(plane.xyz)
+        - plane_cut_dissolve_depth(frag_coord, world_position, instance_index);
+}
+
+// Return how opaque a kept surface is at a distance from the plane of a soft
+// cut. It fades in over the width.
+fn soft_cut_opacity(width: f32, distance: f32) -> f32 {
+    if (width <= 0.0) {
+        return 1.0;
+    }
+    return saturate(distance / width);
+}
+
+// Does screen-door transparency drop a fragment of this opacity? The 4x4 Bayer
+// pattern is fixed to the screen, so the prepass drops the same fragments as
+// the main pass.
+fn is_dithered_out(frag_coord: vec4<f32>, opacity: f32) -> bool {
+    var bayer = array<f32, 16>(
+        0.0, 8.0, 2.0, 10.0,
+        12.0, 4.0, 14.0, 6.0,
+        3.0, 11.0, 1.0, 9.0,
+        15.0, 7.0, 13.0, 5.0,
+    );
+    let p = vec2<u32>(frag_coord.xy) % 4u;
+    return opacity < (bayer[p.y * 4u + p.x] + 0.5) / 16.0;
+}
+
+// Does the material fade by alpha rather than by dithering?
+fn is_alpha_blended(material: StandardMaterial) -> bool {
+    let alpha_mode = material.flags & STANDARD_MATERIAL_FLAGS_ALPHA_MODE_RESERVED_BITS;
+    return alpha_mode != STANDARD_MATERIAL_FLAGS_ALPHA_MODE_OPAQUE
+        && alpha_mode != STANDARD_MATERIAL_FLAGS_ALPHA_MODE_MASK;
+}
+
+// Return how opaque the surface is at a kept fragment of a soft cut.
+fn plane_cut_soft_opacity(in: VertexOutput) -> f32 {
+    let plane = plane_cut_plane(in.instance_index);
+    let distance = plane_cut_distance(in.position, in.world_position, plane, in.instance_index);
+    return soft_cut_opacity(plane_cut_ext.soft_width, distance);
+}
+
+// Return where the view ray through a frag coord hits a world space plane.
+fn plane_ray_hit(xy: vec2<f32>, plane: vec4<f32>) -> vec3<f32> {
+    let ndc = frag_coord_to_ndc(vec4(xy, 1.0, 1.0)).xy;
+    let a = position_ndc_to_world(vec3(ndc, 1.0));
+    let b = position_ndc_to_world(vec3(ndc, 0.5));
+    return a + (b - a) * (plane.w - dot(plane.xyz, a)) / dot(plane.xyz, b - a);
+}
+
+// Return the x axis of a plane's 2D coordinates from its unit normal. It is
+// glam's Vec3::any_orthonormal_vector(), the same as
+// `contour::CrossSection`'s. The y axis is `cross(x_axis, n)`.
+fn plane_x_axis(n: vec3<f32>) -> vec3<f32> {
+    let sign = select(-1.0, 1.0, n.z >= 0.0);
+    let a = -1.0 / (sign + n.z);
+    return vec3(n.x * n.y * a, sign + n.y * n.y * a, -n.y);
+}
+
+// Return a point's 2D coordinates on a plane.
+fn plane_2d(plane: vec4<f32>, position: vec3<f32>) -> vec2<f32> {
+    let n = normalize(plane.xyz);
+    let x_axis = plane_x_axis(n);
+    let y_axis = cross(x_axis, n);
+    let d = position - n * plane.w / length(plane.xyz);
+    return vec2(dot(d, x_axis), dot(d, y_axis));
+}
+
+// Return the UV of a point in the plane's 2D coordinates.
+fn cap_uv(uv: CapUv, p: vec2<f32>) -> vec2<f32> {
+    let c = cos(uv.rotation);
+    let s = sin(uv.rotation);
+    let q = vec2(c * p.x + s * p.y, c * p.y - s * p.x);
+    // Flip v so textures are upright.
+    return q * uv.tiling * vec2(1.0, -1.0) + uv.offset;
+}
+
+// Where a cap is at a frag coord in the plane's 2D coordinates and how far
+// away the next pixels over and up are there.
+struct CapPoint {
+    p: vec2<f32>,
+    dx: vec2<f32>,
+    dy: vec2<f32>,
+}
+
+// Sample a cap's texture. The gradients come from the neighboring pixels
+// rather than derivatives, which need uniform control flow.
+fn cap_texture_sample(
+    t: texture_2d<f32>,
+    s: sampler,
+    uv: CapUv,
+    point: CapPoint,
+) -> vec4<f32> {
+    let origin = cap_uv(uv, vec2(0.0));
+    let ddx = cap_uv(uv, point.dx) - origin;
+    let ddy = cap_uv(uv, point.dy) - origin;
+    return textureSampleGrad(t, s, fract(cap_uv(uv, point.p)), ddx, ddy);
+}
+
+// Return how much of a pixel a pattern's lines or dots cover at a point in
+// the plane's 2D coordinates. A pixel is `pixel` wide there.
+fn cap_pattern_coverage(pattern: CapPattern, p: vec2<f32>, pixel: f32) -> f32 {
+    let c = cos(pattern.angle);
+    let s = sin(pattern.angle);
+    // Rotate the lines onto the x axis.
+    let q = vec2(c * p.x + s * p.y, c * p.y - s * p.x) / pattern.spacing;
+    // The distance to the nearest grid line along each axis.
+    let d = abs(fract(q + 0.5) - 0.5) * pattern.spacing;
+    let r = pattern.line_width * 0.5;
+    let aa = max(pixel, 1e-6) * 0.5;
+    switch pattern.hatch {
+        case CAP_PATTERN_LINES: {
+            return 1.0 - smoothstep(r - aa, r + aa, d.y);
+        }
+        case CAP_PATTERN_CROSSHATCH: {
+            return 1.0 - smoothstep(r - aa, r + aa, min(d.x, d.y));
+        }
+        case CAP_PATTERN_DOTS: {
+            return 1.0 - smoothstep(r - aa, r + aa, length(d));
+        }
+        default: {
+            return 0.0;
+        }
+    }
+}
+
+// Return where the cap is at a frag coord in the plane's 2D coordinates.
+fn plane_cut_cap_2d(frag_coord: vec3<f32>, instance_index: u32) -> vec2<f32> {
+    if ((plane_cut_ext.flags & PLANE_CUT_FLAGS_SCREENSPACE_BIT) != 0u) {
+        return plane_2d(plane_cut_ext.plane, frag_coord);
+    }
+    let hit = plane_ray_hit(frag_coord.xy, plane_cut_plane(instance_index));
+    if ((plane_cut_ext.flags & PLANE_CUT_FLAGS_MODELSPACE_BIT) != 0u) {
+        let local_hit = (get_local_from_world(instance_index) * vec4(hit, 1.0)).xyz;
+        return plane_2d(plane_cut_ext.plane, local_hit);
+    }
+    return plane_2d(plane_cut_ext.plane, hit);
+}
+
+// Return where the cap is at a frag coord.
+fn plane_cut_cap_point(frag_coord: vec4<f32>, instance_index: u32) -> CapPoint {
+    let p = plane_cut_cap_2d(frag_coord.xyz, instance_index);
+    let dx = plane_cut_cap_2d(frag_coord.xyz + vec3(1.0, 0.0, 0.0), instance_index) - p;
+    let dy = plane_cut_cap_2d(frag_coord.xyz + vec3(0.0, 1.0, 0.0), instance_index) - p;
+    return CapPoint(p, dx, dy);
+}
+
+// Return the cap's color with its texture and pattern at a frag coord.
+fn plane_cut_cap_color(frag_coord: vec4<f32>, instance_index: u32) -> vec4<f32> {
+    let pattern = plane_cut_ext.pattern;
+    let textured = (plane_cut_ext.flags & PLANE_CUT_FLAGS_CAP_TEXTURE_BIT) != 0u;
+    let patterned = pattern.hatch != CAP_PATTERN_SOLID && pattern.spacing > 0.0;
+    if (!textured && !patterned) {
+        return plane_cut_ext.color;
+    }
+    let point = plane_cut_cap_point(frag_coord, instance_index);
+    var color = plane_cut_ext.color;
+    if (textured) {
+        color *= cap_texture_sample(
+            plane_cut_cap_texture,
+            plane_cut_cap_sampler,
+            plane_cut_ext.cap_uv,
+            point,
+        );
+    }
+    if (patterned) {
+        let pixel = max(length(point.dx), length(point.dy));
+        color = mix(color, pattern.color, cap_pattern_coverage(pattern, point.p, pixel));
+    }
+    return color;
+}
+
+// Return the cap's world normal at a frag coord with its normal map.
+fn plane_cut_cap_normal(frag_coord: vec4<f32>, instance_index: u32) -> vec3<f32> {
+    let plane = plane_cut_plane(instance_index);
+    if ((plane_cut_ext.flags & PLANE_CUT_FLAGS_CAP_NORMAL_MAP_BIT) == 0u
+        || (plane_cut_ext.flags & PLANE_CUT_FLAGS_SCREENSPACE_BIT) != 0u) {
+        return -normalize(plane.xyz);
+    }
+    let point = plane_cut_cap_point(frag_coord, instance_index);
+    let m = cap_texture_sample(
+        plane_cut_cap_normal_map,
+        plane_cut_cap_normal_sampler,
+        plane_cut_ext.cap_uv,
+        point,
+    ).xyz * 2.0 - 1.0;
+    // The cap faces away from the normal, so (t, b, -n) is its tangent
+    // space, where t and b turn and mirror with `cap_uv`. b is up the
+    // texture, which is flipped in v.
+    let n = normalize(plane_cut_ext.plane.xyz);
+    let x_axis = plane_x_axis(n);
+    let y_axis = cross(x_axis, n);
+    let uv = plane_cut_ext.cap_uv;
+    let c = cos(uv.rotation);
+    let s = sin(uv.rotation);
+    let flip = select(vec2(-1.0), vec2(1.0), uv.tiling >= vec2(0.0));
+    let tangent = flip.x * (c * x_axis + s * y_axis);
+    let bitangent = flip.y * (c * y_axis - s * x_axis);
+    let normal = m.x * tangent + m.y * bitangent - m.z * n;
+    if ((plane_cut_ext.flags & PLANE_CUT_FLAGS_MODELSPACE_BIT) != 0u) {
+        return mesh_normal_local_to_world(normal, instance_index);
+    }
+    return normalize(normal);
+}
+
+// Give a cap its own surface if it has one.
+fn cap_material_apply(cap: CapMaterial, material: StandardMaterial) -> StandardMaterial {
+    if (cap.enabled == 0u) {
+        return material;
+    }
+    var out = material;
+    out.emissive = cap.emissive;
+    out.metallic = cap.metallic;
+    out.perceptual_roughness = cap.perceptual_roughness;
+    out.reflectance = vec3(cap.reflectance);
+    out.clearcoat = cap.clearcoat;
+    out.clearcoat_perceptual_roughness = cap.clearcoat_perceptual_roughness;
+    return out;
+}
+
+// Where the view ray through a fragment hits a cap in world space and its
+// depth.
+struct CapHit {
+    position: vec3<f32>,
+    depth: f32,
+    // False when the back face is not seen through the cut, e.g. an inner
+    // wall seen by a camera inside the mesh.
+    is_cap: bool,
+}
+
+// Return where the view ray through a fragment hits a cap's plane, which is
+// in the space it is tested in. A fragment whose ray does not hit the plane
+// between the camera and itself is not a cap and keeps its own position and
+// depth.
+fn plane_cap_hit(
+    frag_coord: vec4<f32>,
+    world_position: vec3<f32>,
+    depth: f32,
+    plane: vec4<f32>,
+    screen_space: bool,
+) -> CapHit {
+    var position: vec3<f32>;
+    var cap_depth: f32;
+    if (screen_space) {
+        if (abs(plane.z) < 1e-6) {
+            // The plane is parallel to the view ray.
+            return CapHit(world_position, depth, false);
+        }
+        cap_depth = (plane.w - dot(plane.xy, frag_coord.xy)) / plane.z;
+        position = position_ndc_to_world(frag_coord_to_ndc(vec4(frag_coord.xy, cap_depth, 1.0)));
+    } else {
+        position = plane_ray_hit(frag_coord.xy, plane);
+        cap_depth = position_world_to_ndc(position).z;
+    }
+#ifndef UNCLIPPED_DEPTH_ORTHO_EMULATION
+    if (cap_depth > 1.0) {
+        // Behind the camera.
+        return CapHit(world_position, depth, false);
+    }
+#endif
+    // Depth is reversed, so nearer is greater. NaN fails too.
+    if (cap_depth >= depth) {
+        return CapHit(position, cap_depth, true);
+    }
+    return CapHit(world_position, depth, false);
+}
+
+// Return whether a fragment faces the camera. A transform with a negative
+// scale mirrors the mesh and reverses its winding, which flips front_facing.
+fn is_facing(is_front: bool, instance_index: u32) -> bool {
+    return is_front == (sign_determinant_model_3x3m(mesh[instance_index].flags) > 0.0);
+}
+
+// Return where a world position that moves with the mesh in model space was
+// last frame.
+fn previous_position(position: vec3<f32>, instance_index: u32, model_space: bool) -> vec3<f32> {
+    if (model_space) {
+        let local_position = get_local_from_world(instance_index) * vec4(position, 1.0);
+        return (get_previous_world_from_local(instance_index) * local_position).xyz;
+    }
+    return position;
+}
+
+// Return the depth a fragment writes without a cap.
+fn fragment_depth(in: VertexOutput) -> f32 {
+#ifdef UNCLIPPED_DEPTH_ORTHO_EMULATION
+    return in.unclipped_depth;
+#else
+    return in.position.z;
+#endif
+}
+
+// The cap writes its own depth, so the outputs are Bevy's with a depth.
+#ifdef PREPASS_PIPELINE
+#ifdef PREPASS_FRAGMENT
+struct PlaneCutFragmentOutput {
+#ifdef NORMAL_PREPASS
+    @location(0) normal: vec4<f32>,
+#endif
+#ifdef MOTION_VECTOR_PREPASS
+    @location(1) motion_vector: vec2<f32>,
+#endif
+#ifdef DEFERRED_PREPASS
+    @location(2) deferred: vec4<u32>,
+    @location(3) deferred_lighting_pass_id: u32,
+#endif
+    @builtin(frag_depth) frag_depth: f32,
+}
+
+#ifdef DEFERRED_PREPASS
+// Add a depth to the deferred prepass's output.
+fn with_frag_depth(out: FragmentOutput, frag_depth: f32) -> PlaneCutFragmentOutput {
+    var depth_out: PlaneCutFragmentOutput;
+#ifdef NORMAL_PREPASS
+    depth_out.normal = out.normal;
+#endif
+#ifdef MOTION_VECTOR_PREPASS
+    depth_out.motion_vector = out.motion_vector;
+#endif
+    depth_out.deferred = out.deferred;
+    depth_out.deferred_lighting_pass_id = out.deferred_lighting_pass_id;
+    depth_out.frag_depth = frag_depth;
+    return depth_out;
+}
+#endif
+#endif
+#else
+struct PlaneCutFragmentOutput {
+    @location(0) color: vec4<f32>,
+    @builtin(frag_depth) frag_depth: f32,
+}
+#endif
+
+// Return where the view ray through a fragment hits the cap. Only back faces
+// can be caps.
+fn plane_cut_cap_hit(in: VertexOutput, is_front: bool) -> CapHit {
+    if (is_facing(is_front, in.instance_index)) {
+        return CapHit(in.world_position.xyz, fragment_depth(in), false);
+    }
+    return plane_cap_hit(
+        in.position,
+        in.world_position.xyz,
+        fragment_depth(in),
+        plane_cut_plane(in.instance_index),
+        (plane_cut_ext.flags & PLANE_CUT_FLAGS_SCREENSPACE_BIT) != 0u,
+    );
+}
+
+// PLANE_CUT_PREPASS is set by the plugin for the depth, normal, and motion
+// vector prepass, which also draws shadows.
+#ifdef PLANE_CUT_PREPASS
+#ifdef PREPASS_FRAGMENT
+@fragment
+fn fragment(
+    in: VertexOutput,
+    @builtin(front_facing) is_front: bool,
+) -> PlaneCutFragmentOutput {
+    let plane = plane_cut_plane(in.instance_index);
+    if (plane_cut_is_cut(in.position, in.world_position, plane, in.instance_index)) {
+        discard;
+    }
+    prepass_alpha_discard(in);
+
+    var out: PlaneCutFragmentOutput;
+    let hit = plane_cut_cap_hit(in, is_front);
+    if (!hit.is_cap && is_dithered_out(in.position, plane_cut_soft_opacity(in))) {
+        discard;
+    }
+    out.frag_depth = hit.depth;
+#ifdef NORMAL_PREPASS
+    var normal = in.world_normal;
+    if (hit.is_cap) {
+        normal = plane_cut_cap_normal(in.position, in.instance_index);
+    } else if (!is_facing(is_front, in.instance_index)) {
+        // An inner wall faces the camera inside the mesh.
+        normal = -normal;
+    }
+    out.normal = vec4(normal * 0.5 + vec3(0.5), 1.0);
+#endif
+#ifdef MOTION_VECTOR_PREPASS
+    var previous_world_position = in.previous_world_position;
+    if (hit.is_cap) {
+        let model_space = (plane_cut_ext.flags & PLANE_CUT_FLAGS_MODELSPACE_BIT) != 0u;
+        previous_world_position = vec4(previous_position(hit.position, in.instance_index, model_space), 1.0);
+    }
+    out.motion_vector = calculate_motion_vector(vec4(hit.position, 1.0), previous_world_position);
+#endif
+    return out;
+}
+#else
+// Depth only, e.g. shadows. The plane cut plugin adds this fragment stage
+// because without it the cut away portion would still write depth.
+@fragment
+fn fragment(
+    in: VertexOutput,
+    @builtin(front_facing) is_front: bool,
+) -> @builtin(frag_depth) f32 {
+    let plane = plane_cut_plane(in.instance_index);
+    if (plane_cut_is_cut(in.position, in.world_position, plane, in.instance_index)) {
+        discard;
+    }
+    prepass_alpha_discard(in);
+    let hit = plane_cut_cap_hit(in, is_front);
+    if (!hit.is_cap && is_dithered_out(in.position, plane_cut_soft_opacity(in))) {
+        discard;
+    }
+    return hit.depth;
+}
+#endif
+#else
 @fragment
 fn fragment(
     in_: VertexOutput,
     @builtin(front_facing) is_front: bool,
-) -> FragmentOutput {
+) -> PlaneCutFragmentOutput {
     var in = in_;
 
     let shaded = (plane_cut_ext.flags & PLANE_CUT_FLAGS_SHADED_BIT) != 0u;
-    if (!is_front && shaded) {
-        // The in.world_position is not actually correct, but I don't see any
-        // difference visually.
-        in.world_normal = -plane_cut_ext.plane.xyz;
+    let plane = plane_cut_plane(in.instance_index);
+    if (plane_cut_is_cut(in.position, in.world_position, plane, in.instance_index)) {
+        discard;
     }
-    // Generate a PbrInput struct from the StandardMaterial bindings
-    var pbr_input = pbr_input_from_standard_material(in, is_front);
-
-    if (!is_front && shaded) {
-        pbr_input.material.base_color = plane_cut_ext.color;
+    let hit = plane_cut_cap_hit(in, is_front);
+    let is_cap = hit.is_cap;
+    // A back face that is not a cap is an inner wall seen from inside the mesh.
+    let is_wall = !is_cap && !is_facing(is_front, in.instance_index);
+    if (is_cap) {
+        // Light the cap where it is on the plane rather than at the back face.
+        in.world_position = vec4(hit.position, 1.0);
+#ifdef PREPASS_PIPELINE
+#ifdef MOTION_VECTOR_PREPASS
+        let model_space = (plane_cut_ext.flags & PLANE_CUT_FLAGS_MODELSPACE_BIT) != 0u;
+        in.previous_world_position = vec4(previous_position(hit.position, in.instance_index, model_space), 1.0);
+#endif
+#endif
     }
+    if (is_cap && shaded) {
+        in.world_normal = plane_cut_cap_normal(in.position, in.instance_index);
+    }
+    // Generate a PbrInput struct from the StandardMaterial bindings. Only a
+    // wall's normal is flipped to face the camera, and only if double sided.
+    var pbr_input = pbr_input_from_standard_material(in, !is_wall);
+    if (is_wall && (pbr_input.material.flags & STANDARD_MATERIAL_FLAGS_DOUBLE_SIDED_BIT) == 0u) {
+        pbr_input.world_normal = -pbr_input.world_normal;
+        pbr_input.N = -pbr_input.N;
+        pbr_input.clearcoat_N = -pbr_input.clearcoat_N;
+    }
+    in.world_normal = pbr_input.world_normal;
 
-    // Alpha discard
-    pbr_input.material.base_color = alpha_discard(pbr_input.material, pbr_input.material.base_color);
-
-    if ((plane_cut_ext.flags & PLANE_CUT_FLAGS_SCREENSPACE_BIT) != 0u) {
-        // Screenspace
-        if (dot(in.position.xyz, plane_cut_ext.plane.xyz) < plane_cut_ext.plane.w) {
-            discard;
-        }
-    } else {
-        // World space
-        if (dot(in.world_position.xyz, plane_cut_ext.plane.xyz) < plane_cut_ext.plane.w) {
+    if (is_cap && shaded) {
+        pbr_input.material.base_color = plane_cut_cap_color(in.position, in.instance_index);
+        pbr_input.material = cap_material_apply(plane_cut_ext.cap_material, pbr_input.material);
+        // A cap is not transmissive. It blends by its color's alpha instead.
+        pbr_input.material.specular_transmission = 0.0;
+        pbr_input.material.diffuse_transmission = 0.0;
+    }
+    if (!is_cap) {
+        // Emissive survives into the deferred lighting pass.
+        let distance = plane_cut_distance(in.position, in.world_position, plane, in.instance_index);
+        pbr_input.material.emissive += vec4(cut_edge_glow(plane_cut_ext.edge, distance), 0.0);
+        let dissolve = plane_cut_ext.dissolve;
+        let burn = CutEdge(dissolve.edge_color, dissolve.edge_width, 1.0);
+        pbr_input.material.emissive += vec4(cut_edge_glow(burn, distance), 0.0);
+        // Fade out toward the plane of a soft cut.
+        let opacity = soft_cut_opacity(plane_cut_ext.soft_width, distance);
+        if (is_alpha_blended(pbr_input.material)) {
+            pbr_input.material.base_color.a *= opacity;
+        } else if (is_dithered_out(in.position, opacity)) {
             discard;
         }
     }
-    // Object space
-    // XXX: Might have to do object space in the vertex shader.
+
+    // Alpha discard
+    pbr_input.material.base_color = alpha_discard(pbr_input.material, pbr_input.material.base_color);
 
 #ifdef PREPASS_PIPELINE
     // In deferred mode we can't modify anything after that, as lighting is run
     // in a separate fullscreen shader.
-    let out = deferred_output(in, pbr_input);
+    let out = with_frag_depth(deferred_output(in, pbr_input), hit.depth);
 #else
-    var out: FragmentOutput;
+    var out: PlaneCutFragmentOutput;
+    out.frag_depth = hit.depth;
     // Apply lighting
     out.color = apply_pbr_lighting(pbr_input);
 
     out.color = main_pass_post_lighting_processing(pbr_input, out.color);
-    if (!shaded && !is_front) {
-        out.color = plane_cut_ext.color;
+    if (!shaded && is_cap) {
+        out.color = plane_cut_cap_color(in.position, in.instance_index);
+    }
+#ifdef OIT_ENABLED
+    let alpha_mode = pbr_input.material.flags & STANDARD_MATERIAL_FLAGS_ALPHA_MODE_RESERVED_BITS;
+    if (alpha_mode != STANDARD_MATERIAL_FLAGS_ALPHA_MODE_OPAQUE) {
+        // Sort the cap by its own depth in the resolve pass.
+        oit_draw(vec4(in.position.xy, hit.depth, in.position.w), out.color);
+        discard;
     }
+#endif
 #endif
 
     return out;
 }
+#endif
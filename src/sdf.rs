@@ -0,0 +1,218 @@
+use bevy::{
+    app::App,
+    asset::{embedded_asset, Asset, Handle},
+    image::Image,
+    math::{Quat, Vec3, Vec4},
+    pbr::{
+        ExtendedMaterial, MaterialExtension, MaterialExtensionKey, MaterialExtensionPipeline,
+        MaterialPlugin, StandardMaterial,
+    },
+    color::{Color, LinearRgba, ColorToComponents},
+    log::warn,
+    reflect::Reflect,
+    render::{
+        mesh::MeshVertexBufferLayoutRef,
+        render_asset::RenderAssets,
+        render_resource::{
+            AsBindGroup, AsBindGroupShaderType, RenderPipelineDescriptor, ShaderRef, ShaderType,
+            SpecializedMeshPipelineError,
+        },
+        texture::GpuImage,
+    },
+};
+use super::*;
+
+/// The maximum number of shapes in a [`SdfCutExt`].
+pub const SDF_SHAPE_MAX: usize = 8;
+
+pub(crate) fn plugin(app: &mut App) {
+    embedded_asset!(app, "sdf_cut.wgsl");
+    app.add_plugins(MaterialPlugin::<SdfCutMaterial>::default());
+}
+
+/// Type alias for signed distance field cut material.
+pub type SdfCutMaterial = ExtendedMaterial<StandardMaterial, SdfCutExt>;
+
+/// A signed distance field primitive.
+#[derive(Reflect, Debug, Clone, PartialEq)]
+pub enum SdfShape {
+    /// A sphere.
+    Sphere {
+        /// The center.
+        center: Vec3,
+        /// The radius.
+        radius: f32,
+    },
+    /// A box with rounded edges.
+    Cuboid {
+        /// The center.
+        center: Vec3,
+        /// Half the size along each of its axes.
+        half_size: Vec3,
+        /// The rotation of its axes.
+        rotation: Quat,
+        /// The radius of its edges and corners.
+        rounding: f32,
+    },
+    /// A cylinder with round caps.
+    Capsule {
+        /// The center of one cap.
+        start: Vec3,
+        /// The center of the other cap.
+        end: Vec3,
+        /// The radius.
+        radius: f32,
+    },
+}
+
+/// The signed distance field cut extension.
+///
+/// The field is the smooth union of its shapes and its texture. Where it is
+/// negative is cut. The cap is shaded with the field's gradient as its normal.
+#[derive(Asset, AsBindGroup, Reflect, Debug, Clone)]
+#[uniform(106, SdfCutExtUniform)]
+pub struct SdfCutExt {
+    /// The shapes of the field. Respects a maximum of [`SDF_SHAPE_MAX`].
+    pub shapes: Vec<SdfShape>,
+    /// How far shapes blend into each other. Zero is a hard union.
+    pub smoothness: f32,
+    /// An optional 3D texture whose red channel is the signed distance, e.g.,
+    /// `R16Float`.
+    #[texture(107, dimension = "3d")]
+    #[sampler(108)]
+    pub texture: Option<Handle<Image>>,
+    /// The corner of the texture's box with the least coordinates.
+    pub texture_min: Vec3,
+    /// The corner of the texture's box with the greatest coordinates.
+    pub texture_max: Vec3,
+    /// Define the color of the cut.
+    pub color: Color,
+    /// Define the space the field is in.
+    pub space: Space,
+    /// Is the cut shaded or unlit? Shaded is the default. Note: using the
+    /// deferred renderer will not respect an unlit option.
+    pub shaded: bool,
+}
+
+impl Default for SdfCutExt {
+    fn default() -> Self {
+        Self {
+            shapes: vec![SdfShape::Sphere {
+                center: Vec3::ZERO,
+                radius: 1.0,
+            }],
+            smoothness: 0.0,
+            texture: None,
+            texture_min: Vec3::splat(-1.0),
+            texture_max: Vec3::splat(1.0),
+            color: Color::BLACK,
+            space: Space::default(),
+            shaded: true,
+        }
+    }
+}
+
+/// The GPU representation of a [`SdfShape`].
+#[derive(Clone, Default, ShaderType)]
+struct SdfCutShape {
+    a: Vec4,
+    b: Vec4,
+    rotation: Vec4,
+    kind: u32,
+}
+
+/// The GPU representation of the uniform data of a [`SdfCutExt`].
+#[derive(Clone, Default, ShaderType)]
+struct SdfCutExtUniform {
+    shapes: [SdfCutShape; SDF_SHAPE_MAX],
+    texture_min: Vec4,
+    texture_max: Vec4,
+    color: Vec4,
+    smoothness: f32,
+    shape_count: u32,
+    flags: u32,
+}
+
+impl From<&SdfShape> for SdfCutShape {
+    fn from(shape: &SdfShape) -> Self {
+        match *shape {
+            SdfShape::Sphere { center, radius } => SdfCutShape {
+                a: center.extend(radius),
+                rotation: Quat::IDENTITY.into(),
+                kind: 0,
+                ..Default::default()
+            },
+            SdfShape::Cuboid {
+                center,
+                half_size,
+                rotation,
+                rounding,
+            } => SdfCutShape {
+                a: center.extend(rounding),
+                b: half_size.extend(0.0),
+                rotation: rotation.normalize().into(),
+                kind: 1,
+            },
+            SdfShape::Capsule { start, end, radius } => SdfCutShape {
+                a: start.extend(radius),
+                b: end.extend(0.0),
+                rotation: Quat::IDENTITY.into(),
+                kind: 2,
+            },
+        }
+    }
+}
+
+impl AsBindGroupShaderType<SdfCutExtUniform> for SdfCutExt {
+    fn as_bind_group_shader_type(&self, _images: &RenderAssets<GpuImage>) -> SdfCutExtUniform {
+        let mut flags = flags(&self.space, self.shaded);
+        if self.texture.is_some() {
+            flags |= 8;
+        }
+        if self.shapes.len() > SDF_SHAPE_MAX {
+            warn!(
+                "SdfCutExt has {} shapes but at most {} are supported; ignoring the rest.",
+                self.shapes.len(),
+                SDF_SHAPE_MAX
+            );
+        }
+        let mut uniform = SdfCutExtUniform {
+            texture_min: self.texture_min.extend(1.0),
+            texture_max: self.texture_max.extend(1.0),
+            color: LinearRgba::from(self.color).to_f32_array().into(),
+            smoothness: self.smoothness,
+            shape_count: self.shapes.len().min(SDF_SHAPE_MAX) as u32,
+            flags,
+            ..Default::default()
+        };
+        for (i, shape) in self.shapes.iter().take(SDF_SHAPE_MAX).enumerate() {
+            uniform.shapes[i] = shape.into();
+        }
+        uniform
+    }
+}
+
+impl MaterialExtension for SdfCutExt {
+    fn fragment_shader() -> ShaderRef {
+        "embedded://bevy_plane_cut/sdf_cut.wgsl".into()
+    }
+
+    fn prepass_fragment_shader() -> ShaderRef {
+        "embedded://bevy_plane_cut/sdf_cut.wgsl".into()
+    }
+
+    fn deferred_fragment_shader() -> ShaderRef {
+        "embedded://bevy_plane_cut/sdf_cut.wgsl".into()
+    }
+
+    fn specialize(
+        pipeline: &MaterialExtensionPipeline,
+        descriptor: &mut RenderPipelineDescriptor,
+        _layout: &MeshVertexBufferLayoutRef,
+        _key: MaterialExtensionKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        descriptor.primitive.cull_mode = None;
+        specialize_prepass(pipeline, descriptor);
+        Ok(())
+    }
+}
@@ -0,0 +1,272 @@
+#import bevy_pbr::{
+    mesh_view_bindings::view,
+    pbr_fragment::pbr_input_from_standard_material,
+    pbr_functions::alpha_discard,
+    mesh_functions::{get_local_from_world, mesh_normal_local_to_world},
+}
+
+#ifdef PREPASS_PIPELINE
+#import bevy_pbr::{
+    prepass_io::{VertexOutput, FragmentOutput},
+    pbr_deferred_functions::deferred_output,
+    pbr_prepass_functions::{prepass_alpha_discard, calculate_motion_vector},
+}
+#else
+#import bevy_pbr::{
+    forward_io::{VertexOutput, FragmentOutput},
+    pbr_functions::{apply_pbr_lighting, main_pass_post_lighting_processing},
+}
+#endif
+
+struct SdfCutShape {
+    // The center or start in xyz and the radius or rounding in w.
+    a: vec4<f32>,
+    // The half size or end in xyz.
+    b: vec4<f32>,
+    // The rotation of a cuboid as a quaternion.
+    rotation: vec4<f32>,
+    kind: u32,
+}
+
+struct SdfCutExt {
+    shapes: array<SdfCutShape, 8>,
+    texture_min: vec4<f32>,
+    texture_max: vec4<f32>,
+    color: vec4<f32>,
+    smoothness: f32,
+    shape_count: u32,
+    flags: u32,
+}
+const PLANE_CUT_FLAGS_SCREENSPACE_BIT: u32 = 1u;
+const PLANE_CUT_FLAGS_MODELSPACE_BIT: u32 = 2u;
+const PLANE_CUT_FLAGS_SHADED_BIT: u32 = 4u;
+const PLANE_CUT_FLAGS_TEXTURE_BIT: u32 = 8u;
+
+const SDF_CUT_SPHERE: u32 = 0u;
+const SDF_CUT_CUBOID: u32 = 1u;
+const SDF_CUT_CAPSULE: u32 = 2u;
+
+// How close to the surface the cap search stops and how many steps it takes.
+const SDF_CUT_EPSILON: f32 = 1e-3;
+const SDF_CUT_STEPS: i32 = 64;
+
+@group(2) @binding(106)
+var<uniform> sdf_cut_ext: SdfCutExt;
+@group(2) @binding(107)
+var sdf_cut_texture: texture_3d<f32>;
+@group(2) @binding(108)
+var sdf_cut_sampler: sampler;
+
+fn quat_rotate(q: vec4<f32>, v: vec3<f32>) -> vec3<f32> {
+    return v + 2.0 * cross(q.xyz, cross(q.xyz, v) + q.w * v);
+}
+
+fn sdf_cut_shape(shape: SdfCutShape, p: vec3<f32>) -> f32 {
+    switch (shape.kind) {
+        case SDF_CUT_SPHERE: {
+            return length(p - shape.a.xyz) - shape.a.w;
+        }
+        case SDF_CUT_CUBOID: {
+            let q = shape.rotation;
+            let local = quat_rotate(vec4(-q.xyz, q.w), p - shape.a.xyz);
+            let d = abs(local) - (shape.b.xyz - vec3(shape.a.w));
+            return length(max(d, vec3(0.0))) + min(max(d.x, max(d.y, d.z)), 0.0) - shape.a.w;
+        }
+        default: { // SDF_CUT_CAPSULE
+            let u = shape.b.xyz - shape.a.xyz;
+            let h = clamp(dot(p - shape.a.xyz, u) / dot(u, u), 0.0, 1.0);
+            return length(p - (shape.a.xyz + u * h)) - shape.a.w;
+        }
+    }
+}
+
+// Polynomial smooth minimum
+fn smooth_min(a: f32, b: f32, k: f32) -> f32 {
+    if (k <= 0.0) {
+        return min(a, b);
+    }
+    let h = max(k - abs(a - b), 0.0) / k;
+    return min(a, b) - h * h * k * 0.25;
+}
+
+// Return the signed distance at this position. Negative is cut.
+fn sdf_cut_distance(p: vec3<f32>) -> f32 {
+    var d = 3e10;
+    let k = sdf_cut_ext.smoothness;
+    for (var i: u32 = 0u; i < sdf_cut_ext.shape_count; i++) {
+        d = smooth_min(d, sdf_cut_shape(sdf_cut_ext.shapes[i], p), k);
+    }
+    if ((sdf_cut_ext.flags & PLANE_CUT_FLAGS_TEXTURE_BIT) != 0u) {
+        // Outside of its box, add the distance to the box.
+        let lo = sdf_cut_ext.texture_min.xyz;
+        let hi = sdf_cut_ext.texture_max.xyz;
+        let inside = clamp(p, lo, hi);
+        let uvw = (inside - lo) / (hi - lo);
+        let texel = textureSampleLevel(sdf_cut_texture, sdf_cut_sampler, uvw, 0.0).r;
+        d = smooth_min(d, texel + length(p - inside), k);
+    }
+    return d;
+}
+
+// Return the gradient of the field, which points away from the cut.
+fn sdf_cut_gradient(p: vec3<f32>) -> vec3<f32> {
+    // Tetrahedral differences
+    let e = vec2(1.0, -1.0) * 0.5 * SDF_CUT_EPSILON;
+    return normalize(
+        e.xyy * sdf_cut_distance(p + e.xyy) +
+        e.yyx * sdf_cut_distance(p + e.yyx) +
+        e.yxy * sdf_cut_distance(p + e.yxy) +
+        e.xxx * sdf_cut_distance(p + e.xxx)
+    );
+}
+
+struct SdfCutCap {
+    // Does a back face show the cap?
+    is_cap: bool,
+    // The cap's world normal facing the viewer.
+    normal: vec3<f32>,
+}
+
+// Discard the fragment if it is cut. Otherwise return whether a back face
+// shows the cap and its normal.
+//
+// A back face shows the cap if the view ray leaves the cut region before
+// reaching it. That is found by marching from the back face toward the viewer
+// until the field is zero.
+fn sdf_cut(
+    frag_coord: vec4<f32>,
+    world_position: vec4<f32>,
+    instance_index: u32,
+    is_front: bool,
+) -> SdfCutCap {
+    let flags = sdf_cut_ext.flags;
+    var position: vec3<f32>;
+    var origin: vec3<f32>;
+    if ((flags & PLANE_CUT_FLAGS_SCREENSPACE_BIT) != 0u) {
+        // Screenspace, where the near plane has a depth of one.
+        position = frag_coord.xyz;
+        origin = vec3(frag_coord.xy, 1.0);
+    } else if ((flags & PLANE_CUT_FLAGS_MODELSPACE_BIT) != 0u) {
+        // Object space
+        let local_from_world = get_local_from_world(instance_index);
+        position = (local_from_world * world_position).xyz;
+        origin = (local_from_world * vec4(view.world_position, 1.0)).xyz;
+    } else {
+        // World space
+        position = world_position.xyz;
+        origin = view.world_position;
+    }
+    if (sdf_cut_distance(position) < 0.0) {
+        discard;
+    }
+    var cap: SdfCutCap;
+    cap.is_cap = false;
+    if (is_front) {
+        return cap;
+    }
+    // The ray runs from the viewer at t = 0 to this fragment at t = 1.
+    let ray = position - origin;
+    let ray_length = length(ray);
+    var t = 1.0;
+    for (var i = 0; i < SDF_CUT_STEPS; i++) {
+        let p = origin + t * ray;
+        let d = sdf_cut_distance(p);
+        if (d < SDF_CUT_EPSILON) {
+            var normal = -sdf_cut_gradient(p);
+            if ((flags & PLANE_CUT_FLAGS_MODELSPACE_BIT) != 0u) {
+                normal = mesh_normal_local_to_world(normal, instance_index);
+            }
+            cap.is_cap = true;
+            cap.normal = normal;
+            return cap;
+        }
+        t -= d / ray_length;
+        if (t <= 0.0) {
+            break;
+        }
+    }
+    return cap;
+}
+
+// PLANE_CUT_PREPASS is set by the plugin for the depth, normal, and motion
+// vector prepass, which also draws shadows.
+#ifdef PLANE_CUT_PREPASS
+#ifdef PREPASS_FRAGMENT
+@fragment
+fn fragment(
+    in: VertexOutput,
+    @builtin(front_facing) is_front: bool,
+) -> FragmentOutput {
+    let cap = sdf_cut(in.position, in.world_position, in.instance_index, is_front);
+    prepass_alpha_discard(in);
+
+    var out: FragmentOutput;
+#ifdef UNCLIPPED_DEPTH_ORTHO_EMULATION
+    out.frag_depth = in.unclipped_depth;
+#endif
+#ifdef NORMAL_PREPASS
+    var normal = in.world_normal;
+    if (cap.is_cap) {
+        normal = normalize(cap.normal);
+    }
+    out.normal = vec4(normal * 0.5 + vec3(0.5), 1.0);
+#endif
+#ifdef MOTION_VECTOR_PREPASS
+    out.motion_vector = calculate_motion_vector(in.world_position, in.previous_world_position);
+#endif
+    return out;
+}
+#else
+// Depth only, e.g. shadows. The plane cut plugin adds this fragment stage
+// because without it the cut away portion would still write depth.
+@fragment
+fn fragment(
+    in: VertexOutput,
+    @builtin(front_facing) is_front: bool,
+) {
+    sdf_cut(in.position, in.world_position, in.instance_index, is_front);
+    prepass_alpha_discard(in);
+}
+#endif
+#else
+@fragment
+fn fragment(
+    in_: VertexOutput,
+    @builtin(front_facing) is_front: bool,
+) -> FragmentOutput {
+    var in = in_;
+
+    let shaded = (sdf_cut_ext.flags & PLANE_CUT_FLAGS_SHADED_BIT) != 0u;
+    let cap = sdf_cut(in.position, in.world_position, in.instance_index, is_front);
+
+    if (cap.is_cap && shaded) {
+        in.world_normal = cap.normal;
+    }
+    // Generate a PbrInput struct from the StandardMaterial bindings
+    var pbr_input = pbr_input_from_standard_material(in, is_front);
+
+    if (cap.is_cap && shaded) {
+        pbr_input.material.base_color = sdf_cut_ext.color;
+    }
+
+    // Alpha discard
+    pbr_input.material.base_color = alpha_discard(pbr_input.material, pbr_input.material.base_color);
+
+#ifdef PREPASS_PIPELINE
+    // In deferred mode we can't modify anything after that, as lighting is run
+    // in a separate fullscreen shader.
+    let out = deferred_output(in, pbr_input);
+#else
+    var out: FragmentOutput;
+    // Apply lighting
+    out.color = apply_pbr_lighting(pbr_input);
+
+    out.color = main_pass_post_lighting_processing(pbr_input, out.color);
+    if (!shaded && cap.is_cap) {
+        out.color = sdf_cut_ext.color;
+    }
+#endif
+
+    return out;
+}
+#endif
@@ -0,0 +1,557 @@
+use bevy::{
+    log::warn,
+    math::{IVec3, Vec2, Vec3, Vec4},
+    platform::collections::HashMap,
+    render::{
+        mesh::{Indices, Mesh, MeshVertexAttribute, VertexAttributeValues},
+        render_resource::PrimitiveTopology,
+    },
+};
+
+/// Split a mesh by a plane into the part the plane keeps and the part it cuts,
+/// each closed with a cap.
+///
+/// The plane is _(nx, ny, nz, w)_ like [`PlaneCutExt::plane`](crate::PlaneCutExt::plane)
+/// in the mesh's local space, i.e., [`Space::Model`](crate::Space::Model). The
+/// first mesh is what is kept, _n . position >= w_, and the second is what is
+/// cut, _n . position < w_. A part is `None` if it has no triangles.
+///
+/// Every vertex attribute is interpolated where a triangle is split. Integer
+/// attributes, e.g., joint indices, take the value of the nearer vertex. Cap
+/// vertices take the interpolated attributes of the cross section except for
+/// normals, tangents, and UVs, which are the plane's normal, a tangent in the
+/// plane, and the position in the plane. Cross sections are joined across
+/// vertices duplicated at seams; only the open cross sections of meshes that
+/// are not closed go without a cap.
+///
+/// Only triangle lists are supported; other topologies return `(None, None)`.
+pub fn slice_mesh(mesh: &Mesh, plane: Vec4) -> (Option<Mesh>, Option<Mesh>) {
+    let Some((positions, indices)) = positions_and_indices(mesh, "slice_mesh") else {
+        return (None, None);
+    };
+    let mut slicer = Slicer::new(&positions, plane);
+    let Split { kept, cut, segments } = slicer.split(&indices);
+
+    let loops: Vec<Vec<usize>> = slicer
+        .chains(&segments)
+        .into_iter()
+        .filter_map(|(chain, closed)| closed.then_some(chain))
+        .collect();
+    let n = plane.truncate().normalize();
+    let u = n.any_orthonormal_vector();
+    let v = n.cross(u);
+    let kept_mesh = (!kept.is_empty()).then(|| {
+        // The kept part's cap faces into the cut.
+        let cap = Cap { normal: -n, u, v: -v };
+        slicer.build(mesh, &kept, &loops, &cap)
+    });
+    let cut_mesh = (!cut.is_empty()).then(|| {
+        let loops: Vec<Vec<usize>> = loops.iter().map(|l| l.iter().rev().copied().collect()).collect();
+        let cap = Cap { normal: n, u, v };
+        slicer.build(mesh, &cut, &loops, &cap)
+    });
+    (kept_mesh, cut_mesh)
+}
+
+/// Return a triangle list's positions and indices, or `None` with a warning if
+/// it is not one.
+pub(crate) fn positions_and_indices(mesh: &Mesh, caller: &str) -> Option<(Vec<Vec3>, Vec<usize>)> {
+    if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+        warn!("{caller} only supports triangle lists.");
+        return None;
+    }
+    let Some(VertexAttributeValues::Float32x3(positions)) = mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+    else {
+        warn!("{caller} requires Float32x3 positions.");
+        return None;
+    };
+    let positions: Vec<Vec3> = positions.iter().map(|p| Vec3::from_array(*p)).collect();
+    let indices: Vec<usize> = match mesh.indices() {
+        Some(indices) => indices.iter().collect(),
+        None => (0..positions.len()).collect(),
+    };
+    Some((positions, indices))
+}
+
+fn push_triangle(triangles: &mut Vec<[usize; 3]>, [a, b, c]: [usize; 3]) {
+    // A vertex on the plane makes some triangles degenerate.
+    if a != b && b != c && c != a {
+        triangles.push([a, b, c]);
+    }
+}
+
+/// A new vertex interpolated between two vertices of the mesh.
+#[derive(Clone, Copy)]
+struct Source {
+    a: usize,
+    b: usize,
+    t: f32,
+}
+
+impl Source {
+    fn vertex(i: usize) -> Self {
+        Source { a: i, b: i, t: 0.0 }
+    }
+
+    fn nearest(&self) -> usize {
+        if self.t < 0.5 {
+            self.a
+        } else {
+            self.b
+        }
+    }
+}
+
+/// The orientation of a cap. Its loops are counterclockwise in the _(u, v)_
+/// plane facing along its normal.
+struct Cap {
+    normal: Vec3,
+    u: Vec3,
+    v: Vec3,
+}
+
+/// The triangles on each side of the plane and the cross section's edges
+/// oriented for the kept part's cap. Each is of vertex indices of the slicer.
+pub(crate) struct Split {
+    pub kept: Vec<[usize; 3]>,
+    pub cut: Vec<[usize; 3]>,
+    pub segments: Vec<(usize, usize)>,
+}
+
+/// Splits triangles by a plane. Its vertices are the mesh's followed by
+/// those it interpolates where the plane crosses an edge.
+pub(crate) struct Slicer<'a> {
+    positions: &'a [Vec3],
+    distances: Vec<f32>,
+    sources: Vec<Source>,
+    edges: HashMap<(usize, usize), usize>,
+}
+
+impl<'a> Slicer<'a> {
+    pub fn new(positions: &'a [Vec3], plane: Vec4) -> Self {
+        let normal = plane.truncate();
+        Slicer {
+            positions,
+            distances: positions.iter().map(|p| normal.dot(*p) - plane.w).collect(),
+            sources: (0..positions.len()).map(Source::vertex).collect(),
+            edges: HashMap::default(),
+        }
+    }
+
+    /// Split the triangles of a triangle list.
+    pub fn split(&mut self, indices: &[usize]) -> Split {
+        let mut kept = Vec::new();
+        let mut cut = Vec::new();
+        let mut segments = Vec::new();
+        for triangle in indices.chunks_exact(3) {
+            let is_cut = triangle.iter().map(|i| self.distances[*i] < 0.0);
+            let cut_count = is_cut.clone().filter(|c| *c).count();
+            match cut_count {
+                0 => kept.push([triangle[0], triangle[1], triangle[2]]),
+                3 => cut.push([triangle[0], triangle[1], triangle[2]]),
+                _ => {
+                    // Rotate the lone vertex on its side to the front keeping
+                    // the winding.
+                    let lone_is_cut = cut_count == 1;
+                    let k = is_cut.clone().position(|c| c == lone_is_cut).unwrap();
+                    let [l, a, b] = [triangle[k], triangle[(k + 1) % 3], triangle[(k + 2) % 3]];
+                    let la = self.intersect(l, a);
+                    let bl = self.intersect(b, l);
+                    let (lone, other) = if lone_is_cut {
+                        (&mut cut, &mut kept)
+                    } else {
+                        (&mut kept, &mut cut)
+                    };
+                    push_triangle(lone, [l, la, bl]);
+                    push_triangle(other, [la, a, b]);
+                    push_triangle(other, [la, b, bl]);
+                    if la != bl {
+                        segments.push(if lone_is_cut { (la, bl) } else { (bl, la) });
+                    }
+                }
+            }
+        }
+        Split { kept, cut, segments }
+    }
+
+    /// Return the vertex where the plane crosses the edge from `a` to `b`.
+    fn intersect(&mut self, a: usize, b: usize) -> usize {
+        // Order the ends by position so that edges duplicated at seams give the
+        // same position.
+        let (pa, pb) = (self.positions[a], self.positions[b]);
+        let (a, b) = if pa.to_array() < pb.to_array() || (pa == pb && a < b) {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        if let Some(i) = self.edges.get(&(a, b)) {
+            return *i;
+        }
+        let (da, db) = (self.distances[a], self.distances[b]);
+        let t = da / (da - db);
+        let i = if t <= 0.0 {
+            a
+        } else if t >= 1.0 {
+            b
+        } else {
+            self.sources.push(Source { a, b, t });
+            self.sources.len() - 1
+        };
+        self.edges.insert((a, b), i);
+        i
+    }
+
+    /// Return the position of a vertex.
+    pub fn position(&self, i: usize) -> Vec3 {
+        let s = self.sources[i];
+        self.positions[s.a].lerp(self.positions[s.b], s.t)
+    }
+
+    /// Chain the segments into polylines and return whether each is closed.
+    /// Segments are joined by position within a small tolerance so chains
+    /// continue across vertices duplicated at seams.
+    pub fn chains(&self, segments: &[(usize, usize)]) -> Vec<(Vec<usize>, bool)> {
+        let extent = self
+            .positions
+            .iter()
+            .fold(0.0f32, |m, p| m.max(p.abs().max_element()));
+        let epsilon = (extent * 1e-5).max(f32::MIN_POSITIVE);
+        let cell = |p: Vec3| (p / epsilon).floor().as_ivec3();
+        let mut starting: HashMap<IVec3, Vec<usize>> = HashMap::default();
+        let mut ending: HashMap<IVec3, Vec<usize>> = HashMap::default();
+        for (s, (a, b)) in segments.iter().enumerate() {
+            starting.entry(cell(self.position(*a))).or_default().push(s);
+            ending.entry(cell(self.position(*b))).or_default().push(s);
+        }
+        // Return the segments whose end, or start, is near this position.
+        let near = |map: &HashMap<IVec3, Vec<usize>>, p: Vec3, end: bool| {
+            let c = cell(p);
+            let mut found: Vec<(usize, f32)> = (-1..=1)
+                .flat_map(|x| (-1..=1).flat_map(move |y| (-1..=1).map(move |z| IVec3::new(x, y, z))))
+                .filter_map(|offset| map.get(&(c + offset)))
+                .flatten()
+                .map(|s| {
+                    let (a, b) = segments[*s];
+                    (*s, self.position(if end { b } else { a }).distance(p))
+                })
+                .filter(|(_, d)| *d <= epsilon)
+                .collect();
+            found.sort_by(|a, b| a.1.total_cmp(&b.1));
+            found.into_iter().map(|(s, _)| s)
+        };
+        // Start open chains at their heads, segments nothing leads into.
+        let heads = (0..segments.len()).filter(|s| {
+            near(&ending, self.position(segments[*s].0), true).all(|other| other == *s)
+        });
+        let seeds: Vec<usize> = heads.chain(0..segments.len()).collect();
+        let mut used = vec![false; segments.len()];
+        let mut chains = Vec::new();
+        for first in seeds {
+            if used[first] {
+                continue;
+            }
+            used[first] = true;
+            let start = self.position(segments[first].0);
+            let mut chain = vec![segments[first].0];
+            let mut current = segments[first].1;
+            let closed = loop {
+                let p = self.position(current);
+                if p.distance(start) <= epsilon {
+                    break true;
+                }
+                chain.push(current);
+                let Some(s) = near(&starting, p, false).find(|s| !used[*s]) else {
+                    break false;
+                };
+                used[s] = true;
+                current = segments[s].1;
+            };
+            if chain.len() >= if closed { 3 } else { 2 } {
+                chains.push((chain, closed));
+            }
+        }
+        chains
+    }
+
+    /// Build one part from its triangles and cap it with the loops.
+    fn build(&self, mesh: &Mesh, triangles: &[[usize; 3]], loops: &[Vec<usize>], cap: &Cap) -> Mesh {
+        let mut remap: HashMap<usize, u32> = HashMap::default();
+        let mut sources = Vec::new();
+        let mut indices = Vec::with_capacity(triangles.len() * 3);
+        for i in triangles.iter().flatten() {
+            let j = *remap.entry(*i).or_insert_with(|| {
+                sources.push(self.sources[*i]);
+                sources.len() as u32 - 1
+            });
+            indices.push(j);
+        }
+        let side_count = sources.len();
+
+        // Cap vertices are separate so they can have their own normals.
+        let points: Vec<Vec<Vec2>> = loops
+            .iter()
+            .map(|l| {
+                l.iter()
+                    .map(|i| {
+                        let p = self.position(*i);
+                        Vec2::new(p.dot(cap.u), p.dot(cap.v))
+                    })
+                    .collect()
+            })
+            .collect();
+        let mut cap_uvs = Vec::new();
+        for (l, loop_points) in loops.iter().zip(&points) {
+            sources.extend(l.iter().map(|i| self.sources[*i]));
+            cap_uvs.extend(loop_points.iter().map(|p| p.to_array()));
+        }
+        for triangle in triangulate(&points) {
+            indices.extend(triangle.map(|i| (side_count + i) as u32));
+        }
+
+        let mut part = Mesh::new(PrimitiveTopology::TriangleList, mesh.asset_usage);
+        for (attribute, values) in mesh.attributes() {
+            let mut values = interpolate(values, &sources);
+            override_cap(attribute, &mut values, side_count, cap, &cap_uvs);
+            part.insert_attribute(*attribute, values);
+        }
+        part.insert_indices(Indices::U32(indices));
+        part
+    }
+}
+
+/// Give the cap vertices from `start` on the cap's normals, tangents, and UVs.
+fn override_cap(
+    attribute: &MeshVertexAttribute,
+    values: &mut VertexAttributeValues,
+    start: usize,
+    cap: &Cap,
+    uvs: &[[f32; 2]],
+) {
+    match values {
+        VertexAttributeValues::Float32x3(values) if attribute.id == Mesh::ATTRIBUTE_NORMAL.id => {
+            values[start..].fill(cap.normal.to_array());
+        }
+        VertexAttributeValues::Float32x4(values) if attribute.id == Mesh::ATTRIBUTE_TANGENT.id => {
+            values[start..].fill(cap.u.extend(1.0).to_array());
+        }
+        VertexAttributeValues::Float32x2(values) if attribute.id == Mesh::ATTRIBUTE_UV_0.id => {
+            values[start..].copy_from_slice(uvs);
+        }
+        _ => {}
+    }
+}
+
+fn lerp_floats<const N: usize>(values: &[[f32; N]], sources: &[Source]) -> Vec<[f32; N]> {
+    sources
+        .iter()
+        .map(|s| {
+            let (a, b) = (values[s.a], values[s.b]);
+            core::array::from_fn(|k| a[k] + (b[k] - a[k]) * s.t)
+        })
+        .collect()
+}
+
+fn nearest<T: Copy>(values: &[T], sources: &[Source]) -> Vec<T> {
+    sources.iter().map(|s| values[s.nearest()]).collect()
+}
+
+/// Return the attribute's values for the sources.
+fn interpolate(values: &VertexAttributeValues, sources: &[Source]) -> VertexAttributeValues {
+    use VertexAttributeValues::*;
+    match values {
+        Float32(v) => Float32(
+            sources
+                .iter()
+                .map(|s| v[s.a] + (v[s.b] - v[s.a]) * s.t)
+                .collect(),
+        ),
+        Float32x2(v) => Float32x2(lerp_floats(v, sources)),
+        Float32x3(v) => Float32x3(lerp_floats(v, sources)),
+        Float32x4(v) => Float32x4(lerp_floats(v, sources)),
+        Sint32(v) => Sint32(nearest(v, sources)),
+        Uint32(v) => Uint32(nearest(v, sources)),
+        Sint32x2(v) => Sint32x2(nearest(v, sources)),
+        Uint32x2(v) => Uint32x2(nearest(v, sources)),
+        Sint32x3(v) => Sint32x3(nearest(v, sources)),
+        Uint32x3(v) => Uint32x3(nearest(v, sources)),
+        Sint32x4(v) => Sint32x4(nearest(v, sources)),
+        Uint32x4(v) => Uint32x4(nearest(v, sources)),
+        Sint16x2(v) => Sint16x2(nearest(v, sources)),
+        Snorm16x2(v) => Snorm16x2(nearest(v, sources)),
+        Uint16x2(v) => Uint16x2(nearest(v, sources)),
+        Unorm16x2(v) => Unorm16x2(nearest(v, sources)),
+        Sint16x4(v) => Sint16x4(nearest(v, sources)),
+        Snorm16x4(v) => Snorm16x4(nearest(v, sources)),
+        Uint16x4(v) => Uint16x4(nearest(v, sources)),
+        Unorm16x4(v) => Unorm16x4(nearest(v, sources)),
+        Sint8x2(v) => Sint8x2(nearest(v, sources)),
+        Snorm8x2(v) => Snorm8x2(nearest(v, sources)),
+        Uint8x2(v) => Uint8x2(nearest(v, sources)),
+        Unorm8x2(v) => Unorm8x2(nearest(v, sources)),
+        Sint8x4(v) => Sint8x4(nearest(v, sources)),
+        Snorm8x4(v) => Snorm8x4(nearest(v, sources)),
+        Uint8x4(v) => Uint8x4(nearest(v, sources)),
+        Unorm8x4(v) => Unorm8x4(nearest(v, sources)),
+    }
+}
+
+pub(crate) fn signed_area(points: &[Vec2]) -> f32 {
+    let n = points.len();
+    (0..n)
+        .map(|i| points[i].perp_dot(points[(i + 1) % n]))
+        .sum::<f32>()
+        * 0.5
+}
+
+pub(crate) fn contains(polygon: &[Vec2], p: Vec2) -> bool {
+    let n = polygon.len();
+    let mut inside = false;
+    for i in 0..n {
+        let (a, b) = (polygon[i], polygon[(i + 1) % n]);
+        if (a.y > p.y) != (b.y > p.y) && p.x < a.x + (p.y - a.y) / (b.y - a.y) * (b.x - a.x) {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+/// Return the loop each loop is directly inside of, if any.
+pub(crate) fn parents(loops: &[Vec<Vec2>]) -> Vec<Option<usize>> {
+    let areas: Vec<f32> = loops.iter().map(|l| signed_area(l).abs()).collect();
+    (0..loops.len())
+        .map(|i| {
+            (0..loops.len())
+                .filter(|j| *j != i && areas[*j] > areas[i] && contains(&loops[*j], loops[i][0]))
+                .min_by(|a, b| areas[*a].total_cmp(&areas[*b]))
+        })
+        .collect()
+}
+
+/// Do the segments from `a` to `b` and from `c` to `d` cross other than at
+/// their ends?
+fn segments_cross(a: Vec2, b: Vec2, c: Vec2, d: Vec2) -> bool {
+    let d1 = (b - a).perp_dot(c - a);
+    let d2 = (b - a).perp_dot(d - a);
+    let d3 = (d - c).perp_dot(a - c);
+    let d4 = (d - c).perp_dot(b - c);
+    d1 * d2 < 0.0 && d3 * d4 < 0.0
+}
+
+/// Triangulate polygons with holes. Outer loops are counterclockwise and
+/// holes clockwise. Return triangles of indices into the loops' points in
+/// order.
+fn triangulate(loops: &[Vec<Vec2>]) -> Vec<[usize; 3]> {
+    let points: Vec<Vec2> = loops.iter().flatten().copied().collect();
+    let mut offsets = Vec::with_capacity(loops.len());
+    let mut offset = 0;
+    for l in loops {
+        offsets.push(offset);
+        offset += l.len();
+    }
+    let ids = |i: usize| -> Vec<usize> { (offsets[i]..offsets[i] + loops[i].len()).collect() };
+    let parents = parents(loops);
+    let mut triangles = Vec::new();
+    for outer in 0..loops.len() {
+        if signed_area(&loops[outer]) <= 0.0 {
+            continue;
+        }
+        let mut holes: Vec<Vec<usize>> = (0..loops.len())
+            .filter(|h| parents[*h] == Some(outer) && signed_area(&loops[*h]) < 0.0)
+            .map(ids)
+            .collect();
+        let mut polygon = ids(outer);
+        // Bridge the holes from the rightmost in.
+        let rightmost = |hole: &Vec<usize>| {
+            (0..hole.len())
+                .max_by(|a, b| points[hole[*a]].x.total_cmp(&points[hole[*b]].x))
+                .unwrap()
+        };
+        holes.sort_by(|a, b| points[b[rightmost(b)]].x.total_cmp(&points[a[rightmost(a)]].x));
+        for h in 0..holes.len() {
+            let hole = &holes[h];
+            let m = rightmost(hole);
+            let pm = points[hole[m]];
+            let edges = |l: &Vec<usize>| -> Vec<(Vec2, Vec2)> {
+                (0..l.len())
+                    .map(|i| (points[l[i]], points[l[(i + 1) % l.len()]]))
+                    .collect()
+            };
+            let mut blocking = edges(&polygon);
+            for other in &holes[h..] {
+                blocking.extend(edges(other));
+            }
+            let bridge = (0..polygon.len())
+                .filter(|i| {
+                    let p = points[polygon[*i]];
+                    !blocking.iter().any(|(a, b)| segments_cross(pm, p, *a, *b))
+                })
+                .min_by(|a, b| {
+                    let da = points[polygon[*a]].distance_squared(pm);
+                    let db = points[polygon[*b]].distance_squared(pm);
+                    da.total_cmp(&db)
+                });
+            let Some(p) = bridge else {
+                continue;
+            };
+            let mut spliced = polygon[..=p].to_vec();
+            spliced.extend((0..=hole.len()).map(|k| hole[(m + k) % hole.len()]));
+            spliced.extend_from_slice(&polygon[p..]);
+            polygon = spliced;
+        }
+        ear_clip(&points, polygon, &mut triangles);
+    }
+    triangles
+}
+
+/// Triangulate a counterclockwise simple polygon that may touch itself where
+/// holes are bridged.
+fn ear_clip(points: &[Vec2], mut polygon: Vec<usize>, triangles: &mut Vec<[usize; 3]>) {
+    while polygon.len() > 3 {
+        let n = polygon.len();
+        let ear = (0..n).find(|i| {
+            let [a, b, c] = [polygon[(i + n - 1) % n], polygon[*i], polygon[(i + 1) % n]];
+            let [pa, pb, pc] = [points[a], points[b], points[c]];
+            if (pb - pa).perp_dot(pc - pa) <= 0.0 {
+                return false;
+            }
+            !polygon.iter().any(|j| {
+                let p = points[*j];
+                p != pa
+                    && p != pb
+                    && p != pc
+                    && (pb - pa).perp_dot(p - pa) >= 0.0
+                    && (pc - pb).perp_dot(p - pb) >= 0.0
+                    && (pa - pc).perp_dot(p - pc) >= 0.0
+            })
+        });
+        match ear {
+            Some(i) => {
+                triangles.push([polygon[(i + n - 1) % n], polygon[i], polygon[(i + 1) % n]]);
+                polygon.remove(i);
+            }
+            None => {
+                // Only degenerate vertices are left. Drop the flattest.
+                let flattest = (0..n)
+                    .min_by(|x, y| {
+                        let area = |i: usize| {
+                            let [pa, pb, pc] = [
+                                points[polygon[(i + n - 1) % n]],
+                                points[polygon[i]],
+                                points[polygon[(i + 1) % n]],
+                            ];
+                            (pb - pa).perp_dot(pc - pa).abs()
+                        };
+                        area(*x).total_cmp(&area(*y))
+                    })
+                    .unwrap();
+                polygon.remove(flattest);
+            }
+        }
+    }
+    if polygon.len() == 3 {
+        let [pa, pb, pc] = [points[polygon[0]], points[polygon[1]], points[polygon[2]]];
+        if (pb - pa).perp_dot(pc - pa) > 0.0 {
+            triangles.push([polygon[0], polygon[1], polygon[2]]);
+        }
+    }
+}
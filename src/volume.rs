@@ -0,0 +1,192 @@
+use bevy::{
+    app::App,
+    asset::{embedded_asset, Asset},
+    math::{Quat, Vec3, Vec4},
+    pbr::{
+        ExtendedMaterial, MaterialExtension, MaterialExtensionKey, MaterialExtensionPipeline,
+        MaterialPlugin, StandardMaterial,
+    },
+    color::{Color, LinearRgba, ColorToComponents},
+    reflect::Reflect,
+    render::{
+        mesh::MeshVertexBufferLayoutRef,
+        render_asset::RenderAssets,
+        render_resource::{
+            AsBindGroup, AsBindGroupShaderType, RenderPipelineDescriptor, ShaderRef, ShaderType,
+            SpecializedMeshPipelineError,
+        },
+        texture::GpuImage,
+    },
+};
+use super::*;
+
+pub(crate) fn plugin(app: &mut App) {
+    embedded_asset!(app, "volume_cut.wgsl");
+    app.add_plugins(MaterialPlugin::<VolumeCutMaterial>::default());
+}
+
+/// Type alias for volume cut material.
+pub type VolumeCutMaterial = ExtendedMaterial<StandardMaterial, VolumeCutExt>;
+
+/// A volume to cut with.
+#[derive(Reflect, Debug, Clone, PartialEq)]
+pub enum CutVolume {
+    /// A sphere.
+    Sphere {
+        /// The center.
+        center: Vec3,
+        /// The radius.
+        radius: f32,
+    },
+    /// A box.
+    Cuboid {
+        /// The center.
+        center: Vec3,
+        /// Half the size along each of its axes.
+        half_size: Vec3,
+        /// The rotation of its axes.
+        rotation: Quat,
+    },
+    /// A cylinder with flat caps.
+    Cylinder {
+        /// The center of one cap.
+        start: Vec3,
+        /// The center of the other cap.
+        end: Vec3,
+        /// The radius.
+        radius: f32,
+    },
+    /// A cylinder with round caps.
+    Capsule {
+        /// The center of one cap.
+        start: Vec3,
+        /// The center of the other cap.
+        end: Vec3,
+        /// The radius.
+        radius: f32,
+    },
+}
+
+impl Default for CutVolume {
+    fn default() -> Self {
+        CutVolume::Sphere {
+            center: Vec3::ZERO,
+            radius: 1.0,
+        }
+    }
+}
+
+/// The volume cut extension.
+///
+/// Cuts what is inside the volume, or outside of it if inverted. The cap is
+/// the volume's surface with its normal.
+#[derive(Asset, AsBindGroup, Reflect, Debug, Clone)]
+#[uniform(105, VolumeCutExtUniform)]
+pub struct VolumeCutExt {
+    /// The volume that cuts.
+    pub volume: CutVolume,
+    /// Define the color of the cut.
+    pub color: Color,
+    /// Define the space the volume is tested in.
+    pub space: Space,
+    /// Is the cut shaded or unlit? Shaded is the default. Note: using the
+    /// deferred renderer will not respect an unlit option.
+    pub shaded: bool,
+    /// Cut outside of the volume instead of inside it, e.g., to isolate a
+    /// room.
+    pub invert: bool,
+}
+
+impl Default for VolumeCutExt {
+    fn default() -> Self {
+        Self {
+            volume: CutVolume::default(),
+            color: Color::BLACK,
+            space: Space::default(),
+            shaded: true,
+            invert: false,
+        }
+    }
+}
+
+/// The GPU representation of the uniform data of a [`VolumeCutExt`].
+#[derive(Clone, Default, ShaderType)]
+struct VolumeCutExtUniform {
+    start: Vec4,
+    end: Vec4,
+    rotation: Vec4,
+    size: Vec4,
+    color: Vec4,
+    shape: u32,
+    flags: u32,
+}
+
+impl AsBindGroupShaderType<VolumeCutExtUniform> for VolumeCutExt {
+    fn as_bind_group_shader_type(&self, _images: &RenderAssets<GpuImage>) -> VolumeCutExtUniform {
+        let mut flags = flags(&self.space, self.shaded);
+        if self.invert {
+            flags |= 8;
+        }
+        let mut uniform = VolumeCutExtUniform {
+            rotation: Quat::IDENTITY.into(),
+            color: LinearRgba::from(self.color).to_f32_array().into(),
+            flags,
+            ..Default::default()
+        };
+        match self.volume {
+            CutVolume::Sphere { center, radius } => {
+                uniform.shape = 0;
+                uniform.start = center.extend(1.0);
+                uniform.size = Vec4::splat(radius);
+            }
+            CutVolume::Cuboid {
+                center,
+                half_size,
+                rotation,
+            } => {
+                uniform.shape = 1;
+                uniform.start = center.extend(1.0);
+                uniform.size = half_size.extend(0.0);
+                uniform.rotation = rotation.normalize().into();
+            }
+            CutVolume::Cylinder { start, end, radius } => {
+                uniform.shape = 2;
+                uniform.start = start.extend(1.0);
+                uniform.end = end.extend(1.0);
+                uniform.size = Vec4::splat(radius);
+            }
+            CutVolume::Capsule { start, end, radius } => {
+                uniform.shape = 3;
+                uniform.start = start.extend(1.0);
+                uniform.end = end.extend(1.0);
+                uniform.size = Vec4::splat(radius);
+            }
+        }
+        uniform
+    }
+}
+
+impl MaterialExtension for VolumeCutExt {
+    fn fragment_shader() -> ShaderRef {
+        "embedded://bevy_plane_cut/volume_cut.wgsl".into()
+    }
+
+    fn prepass_fragment_shader() -> ShaderRef {
+        "embedded://bevy_plane_cut/volume_cut.wgsl".into()
+    }
+
+    fn deferred_fragment_shader() -> ShaderRef {
+        "embedded://bevy_plane_cut/volume_cut.wgsl".into()
+    }
+
+    fn specialize(
+        pipeline: &MaterialExtensionPipeline,
+        descriptor: &mut RenderPipelineDescriptor,
+        _layout: &MeshVertexBufferLayoutRef,
+        _key: MaterialExtensionKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        descriptor.primitive.cull_mode = None;
+        specialize_prepass(pipeline, descriptor);
+        Ok(())
+    }
+}
@@ -0,0 +1,384 @@
+#import bevy_pbr::{
+    mesh_view_bindings::view,
+    pbr_fragment::pbr_input_from_standard_material,
+    pbr_functions::alpha_discard,
+    mesh_functions::{get_local_from_world, mesh_normal_local_to_world},
+}
+
+#ifdef PREPASS_PIPELINE
+#import bevy_pbr::{
+    prepass_io::{VertexOutput, FragmentOutput},
+    pbr_deferred_functions::deferred_output,
+    pbr_prepass_functions::{prepass_alpha_discard, calculate_motion_vector},
+}
+#else
+#import bevy_pbr::{
+    forward_io::{VertexOutput, FragmentOutput},
+    pbr_functions::{apply_pbr_lighting, main_pass_post_lighting_processing},
+}
+#endif
+
+struct VolumeCutExt {
+    // The center or the start of the volume.
+    start: vec4<f32>,
+    // The end of a cylinder or capsule.
+    end: vec4<f32>,
+    // The rotation of a cuboid as a quaternion.
+    rotation: vec4<f32>,
+    // The half size of a cuboid or the radius in x.
+    size: vec4<f32>,
+    color: vec4<f32>,
+    shape: u32,
+    flags: u32,
+}
+const PLANE_CUT_FLAGS_SCREENSPACE_BIT: u32 = 1u;
+const PLANE_CUT_FLAGS_MODELSPACE_BIT: u32 = 2u;
+const PLANE_CUT_FLAGS_SHADED_BIT: u32 = 4u;
+const PLANE_CUT_FLAGS_INVERT_BIT: u32 = 8u;
+
+const VOLUME_CUT_SPHERE: u32 = 0u;
+const VOLUME_CUT_CUBOID: u32 = 1u;
+const VOLUME_CUT_CYLINDER: u32 = 2u;
+const VOLUME_CUT_CAPSULE: u32 = 3u;
+
+// The interval of a ray that misses.
+const VOLUME_CUT_MISS: vec2<f32> = vec2(3e10, -3e10);
+
+@group(2) @binding(105)
+var<uniform> volume_cut_ext: VolumeCutExt;
+
+fn quat_rotate(q: vec4<f32>, v: vec3<f32>) -> vec3<f32> {
+    return v + 2.0 * cross(q.xyz, cross(q.xyz, v) + q.w * v);
+}
+
+// Return a position in the cuboid's space.
+fn volume_cut_cuboid_local(p: vec3<f32>) -> vec3<f32> {
+    let q = volume_cut_ext.rotation;
+    return quat_rotate(vec4(-q.xyz, q.w), p - volume_cut_ext.start.xyz);
+}
+
+// Return the closest point to `p` on the segment from start to end, the axis
+// of a cylinder or capsule.
+fn volume_cut_closest_on_axis(p: vec3<f32>) -> vec3<f32> {
+    let a = volume_cut_ext.start.xyz;
+    let u = volume_cut_ext.end.xyz - a;
+    return a + u * clamp(dot(p - a, u) / dot(u, u), 0.0, 1.0);
+}
+
+// Is this position inside the volume?
+fn volume_cut_inside(p: vec3<f32>) -> bool {
+    let r = volume_cut_ext.size.x;
+    switch (volume_cut_ext.shape) {
+        case VOLUME_CUT_SPHERE: {
+            return length(p - volume_cut_ext.start.xyz) < r;
+        }
+        case VOLUME_CUT_CUBOID: {
+            return all(abs(volume_cut_cuboid_local(p)) < volume_cut_ext.size.xyz);
+        }
+        case VOLUME_CUT_CYLINDER: {
+            let a = volume_cut_ext.start.xyz;
+            let u = volume_cut_ext.end.xyz - a;
+            let h = dot(p - a, u) / dot(u, u);
+            return h > 0.0 && h < 1.0 && length(p - (a + u * h)) < r;
+        }
+        default: { // VOLUME_CUT_CAPSULE
+            return length(p - volume_cut_closest_on_axis(p)) < r;
+        }
+    }
+}
+
+// Return the outward normal of the volume's surface at this position.
+fn volume_cut_normal(p: vec3<f32>) -> vec3<f32> {
+    switch (volume_cut_ext.shape) {
+        case VOLUME_CUT_SPHERE: {
+            return normalize(p - volume_cut_ext.start.xyz);
+        }
+        case VOLUME_CUT_CUBOID: {
+            // The face is the axis the position is furthest along relative to
+            // the half size.
+            let local = volume_cut_cuboid_local(p);
+            let d = abs(local) / volume_cut_ext.size.xyz;
+            var n = vec3(0.0, 0.0, sign(local.z));
+            if (d.x >= d.y && d.x >= d.z) {
+                n = vec3(sign(local.x), 0.0, 0.0);
+            } else if (d.y >= d.z) {
+                n = vec3(0.0, sign(local.y), 0.0);
+            }
+            return quat_rotate(volume_cut_ext.rotation, n);
+        }
+        case VOLUME_CUT_CYLINDER: {
+            let a = volume_cut_ext.start.xyz;
+            let u = volume_cut_ext.end.xyz - a;
+            let h = dot(p - a, u) / dot(u, u);
+            let radial = p - (a + u * h);
+            // The position is on a cap if it is further past the cap than
+            // past the side.
+            let half_length = 0.5 * length(u);
+            let past_cap = abs(h - 0.5) * 2.0 * half_length - half_length;
+            let past_side = length(radial) - volume_cut_ext.size.x;
+            if (past_cap > past_side) {
+                return normalize(u) * sign(h - 0.5);
+            }
+            return normalize(radial);
+        }
+        default: { // VOLUME_CUT_CAPSULE
+            return normalize(p - volume_cut_closest_on_axis(p));
+        }
+    }
+}
+
+// Return the interval of the ray o + t d inside a sphere.
+fn volume_cut_sphere_interval(o: vec3<f32>, d: vec3<f32>, c: vec3<f32>, r: f32) -> vec2<f32> {
+    let oc = o - c;
+    let a = dot(d, d);
+    let b = dot(oc, d);
+    let h = b * b - a * (dot(oc, oc) - r * r);
+    if (h < 0.0) {
+        return VOLUME_CUT_MISS;
+    }
+    let s = sqrt(h);
+    return vec2(-b - s, -b + s) / a;
+}
+
+// Return the interval of the ray o + t d inside a capped cylinder from a to b.
+fn volume_cut_cylinder_interval(
+    o: vec3<f32>,
+    d: vec3<f32>,
+    a: vec3<f32>,
+    b: vec3<f32>,
+    r: f32,
+) -> vec2<f32> {
+    let axis_length = length(b - a);
+    let u = (b - a) / axis_length;
+    let oa = o - a;
+    let od = dot(oa, u);
+    let dd = dot(d, u);
+    // Between the caps
+    var slab = vec2(-3e10, 3e10);
+    if (abs(dd) < 1e-8) {
+        if (od < 0.0 || od > axis_length) {
+            return VOLUME_CUT_MISS;
+        }
+    } else {
+        let t0 = -od / dd;
+        let t1 = (axis_length - od) / dd;
+        slab = vec2(min(t0, t1), max(t0, t1));
+    }
+    // Within the radius
+    let dp = d - u * dd;
+    let op = oa - u * od;
+    let qa = dot(dp, dp);
+    let qb = dot(op, dp);
+    let qc = dot(op, op) - r * r;
+    var side = vec2(-3e10, 3e10);
+    if (qa < 1e-12) {
+        if (qc > 0.0) {
+            return VOLUME_CUT_MISS;
+        }
+    } else {
+        let h = qb * qb - qa * qc;
+        if (h < 0.0) {
+            return VOLUME_CUT_MISS;
+        }
+        let s = sqrt(h);
+        side = vec2(-qb - s, -qb + s) / qa;
+    }
+    return vec2(max(slab.x, side.x), min(slab.y, side.y));
+}
+
+// Return the interval of the ray o + t d inside the volume. It is empty, i.e.,
+// x > y, if the ray misses.
+fn volume_cut_interval(o: vec3<f32>, d: vec3<f32>) -> vec2<f32> {
+    let a = volume_cut_ext.start.xyz;
+    let b = volume_cut_ext.end.xyz;
+    let r = volume_cut_ext.size.x;
+    switch (volume_cut_ext.shape) {
+        case VOLUME_CUT_SPHERE: {
+            return volume_cut_sphere_interval(o, d, a, r);
+        }
+        case VOLUME_CUT_CUBOID: {
+            // Slabs in the cuboid's space
+            let q = volume_cut_ext.rotation;
+            let lo = volume_cut_cuboid_local(o);
+            var ld = quat_rotate(vec4(-q.xyz, q.w), d);
+            ld = select(ld, vec3(1e-8), abs(ld) < vec3(1e-8));
+            let t0 = (-volume_cut_ext.size.xyz - lo) / ld;
+            let t1 = (volume_cut_ext.size.xyz - lo) / ld;
+            let t_min = min(t0, t1);
+            let t_max = max(t0, t1);
+            return vec2(max(max(t_min.x, t_min.y), t_min.z), min(min(t_max.x, t_max.y), t_max.z));
+        }
+        case VOLUME_CUT_CYLINDER: {
+            return volume_cut_cylinder_interval(o, d, a, b, r);
+        }
+        default: { // VOLUME_CUT_CAPSULE
+            // A capsule is convex, so its interval is the union of its parts'.
+            let c = volume_cut_cylinder_interval(o, d, a, b, r);
+            let s0 = volume_cut_sphere_interval(o, d, a, r);
+            let s1 = volume_cut_sphere_interval(o, d, b, r);
+            var t = VOLUME_CUT_MISS;
+            for (var i = 0; i < 3; i++) {
+                var part = c;
+                if (i == 1) {
+                    part = s0;
+                } else if (i == 2) {
+                    part = s1;
+                }
+                if (part.x <= part.y) {
+                    t = vec2(min(t.x, part.x), max(t.y, part.y));
+                }
+            }
+            return t;
+        }
+    }
+}
+
+struct VolumeCutCap {
+    // Does a back face show the cap?
+    is_cap: bool,
+    // The cap's world normal facing the viewer.
+    normal: vec3<f32>,
+}
+
+// Discard the fragment if it is cut. Otherwise return whether a back face
+// shows the cap and its normal.
+//
+// Like the planes of nplane_cut.wgsl, a back face shows the cap if the view
+// ray leaves the cut region before reaching it. When the inside is cut that
+// is where the ray leaves the volume. When the outside is cut, it is where the
+// ray enters it.
+fn volume_cut(
+    frag_coord: vec4<f32>,
+    world_position: vec4<f32>,
+    instance_index: u32,
+    is_front: bool,
+) -> VolumeCutCap {
+    let flags = volume_cut_ext.flags;
+    var position: vec3<f32>;
+    var origin: vec3<f32>;
+    if ((flags & PLANE_CUT_FLAGS_SCREENSPACE_BIT) != 0u) {
+        // Screenspace, where the near plane has a depth of one.
+        position = frag_coord.xyz;
+        origin = vec3(frag_coord.xy, 1.0);
+    } else if ((flags & PLANE_CUT_FLAGS_MODELSPACE_BIT) != 0u) {
+        // Object space
+        let local_from_world = get_local_from_world(instance_index);
+        position = (local_from_world * world_position).xyz;
+        origin = (local_from_world * vec4(view.world_position, 1.0)).xyz;
+    } else {
+        // World space
+        position = world_position.xyz;
+        origin = view.world_position;
+    }
+    let invert = (flags & PLANE_CUT_FLAGS_INVERT_BIT) != 0u;
+    if (volume_cut_inside(position) != invert) {
+        discard;
+    }
+    var cap: VolumeCutCap;
+    cap.is_cap = false;
+    if (is_front) {
+        return cap;
+    }
+    // The ray runs from the viewer at t = 0 to this fragment at t = 1.
+    let t = volume_cut_interval(origin, position - origin);
+    if (t.x > t.y) {
+        return cap;
+    }
+    let t_cap = select(t.y, t.x, invert);
+    if (t_cap <= 0.0 || t_cap >= 1.0) {
+        return cap;
+    }
+    var normal = volume_cut_normal(mix(origin, position, t_cap));
+    if (!invert) {
+        normal = -normal;
+    }
+    if ((flags & PLANE_CUT_FLAGS_MODELSPACE_BIT) != 0u) {
+        normal = mesh_normal_local_to_world(normal, instance_index);
+    }
+    cap.is_cap = true;
+    cap.normal = normal;
+    return cap;
+}
+
+// PLANE_CUT_PREPASS is set by the plugin for the depth, normal, and motion
+// vector prepass, which also draws shadows.
+#ifdef PLANE_CUT_PREPASS
+#ifdef PREPASS_FRAGMENT
+@fragment
+fn fragment(
+    in: VertexOutput,
+    @builtin(front_facing) is_front: bool,
+) -> FragmentOutput {
+    let cap = volume_cut(in.position, in.world_position, in.instance_index, is_front);
+    prepass_alpha_discard(in);
+
+    var out: FragmentOutput;
+#ifdef UNCLIPPED_DEPTH_ORTHO_EMULATION
+    out.frag_depth = in.unclipped_depth;
+#endif
+#ifdef NORMAL_PREPASS
+    var normal = in.world_normal;
+    if (cap.is_cap) {
+        normal = normalize(cap.normal);
+    }
+    out.normal = vec4(normal * 0.5 + vec3(0.5), 1.0);
+#endif
+#ifdef MOTION_VECTOR_PREPASS
+    out.motion_vector = calculate_motion_vector(in.world_position, in.previous_world_position);
+#endif
+    return out;
+}
+#else
+// Depth only, e.g. shadows. The plane cut plugin adds this fragment stage
+// because without it the cut away portion would still write depth.
+@fragment
+fn fragment(
+    in: VertexOutput,
+    @builtin(front_facing) is_front: bool,
+) {
+    volume_cut(in.position, in.world_position, in.instance_index, is_front);
+    prepass_alpha_discard(in);
+}
+#endif
+#else
+@fragment
+fn fragment(
+    in_: VertexOutput,
+    @builtin(front_facing) is_front: bool,
+) -> FragmentOutput {
+    var in = in_;
+
+    let shaded = (volume_cut_ext.flags & PLANE_CUT_FLAGS_SHADED_BIT) != 0u;
+    let cap = volume_cut(in.position, in.world_position, in.instance_index, is_front);
+
+    if (cap.is_cap && shaded) {
+        in.world_normal = cap.normal;
+    }
+    // Generate a PbrInput struct from the StandardMaterial bindings
+    var pbr_input = pbr_input_from_standard_material(in, is_front);
+
+    if (cap.is_cap && shaded) {
+        pbr_input.material.base_color = volume_cut_ext.color;
+    }
+
+    // Alpha discard
+    pbr_input.material.base_color = alpha_discard(pbr_input.material, pbr_input.material.base_color);
+
+#ifdef PREPASS_PIPELINE
+    // In deferred mode we can't modify anything after that, as lighting is run
+    // in a separate fullscreen shader.
+    let out = deferred_output(in, pbr_input);
+#else
+    var out: FragmentOutput;
+    // Apply lighting
+    out.color = apply_pbr_lighting(pbr_input);
+
+    out.color = main_pass_post_lighting_processing(pbr_input, out.color);
+    if (!shaded && cap.is_cap) {
+        out.color = volume_cut_ext.color;
+    }
+#endif
+
+    return out;
+}
+#endif
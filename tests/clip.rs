@@ -0,0 +1,108 @@
+use bevy::prelude::*;
+use bevy_plane_cut::{
+    nplane::{Combine, NPlaneCutExt, PLANE_MAX},
+    CutPoint, Dissolve, PlaneCutExt, Space,
+};
+
+#[test]
+fn model_space_cut_moves_with_mesh() {
+    let cut = PlaneCutExt {
+        plane: Vec4::new(0.0, 1.0, 0.0, 0.0),
+        space: Space::Model,
+        ..default()
+    };
+    let transform = GlobalTransform::from(
+        Transform::from_xyz(0.0, 2.0, 0.0).with_rotation(Quat::from_rotation_z(std::f32::consts::PI)),
+    );
+    let point = |position| CutPoint {
+        transform,
+        ..CutPoint::from(position)
+    };
+    // The mesh is upside down, so its local up is world down.
+    assert!(cut.is_clipped(point(Vec3::new(0.0, 2.5, 0.0))));
+    assert!(!cut.is_clipped(point(Vec3::new(0.0, 1.5, 0.0))));
+    assert!((cut.distance(point(Vec3::new(0.0, 1.5, 0.0))) - 0.5).abs() < 1e-5);
+}
+
+#[test]
+fn dissolve_agrees_with_distance() {
+    let cut = PlaneCutExt {
+        plane: Vec4::new(0.0, 2.0, 0.0, 0.0),
+        dissolve: Some(Dissolve {
+            speed: 0.5,
+            ..default()
+        }),
+        ..default()
+    };
+    let mut clipped = 0;
+    for i in 0..200 {
+        let x = i as f32 * 0.037;
+        let point = CutPoint {
+            time: 1.0,
+            ..CutPoint::from(Vec3::new(x, 0.5 + x.sin() * 0.2, -x))
+        };
+        let distance = cut.distance(point);
+        assert_eq!(cut.is_clipped(point), distance < 0.0, "at {x}");
+        clipped += distance.is_sign_negative() as u32;
+    }
+    // The clock moves the plane up 0.5 and the noise cuts past it, so some
+    // points around y = 0.5 are cut and some are not.
+    assert!(clipped > 0 && clipped < 200);
+}
+
+#[test]
+fn nplane_union_only_cuts_where_every_plane_does() {
+    let mut cut = NPlaneCutExt::try_new(vec![
+        (Vec4::new(1.0, 0.0, 0.0, 0.0), Color::BLACK),
+        (Vec4::new(0.0, 0.0, 1.0, 0.0), Color::BLACK),
+    ])
+    .unwrap();
+    let corner = Vec3::new(-1.0, 0.0, 2.0);
+    assert!(cut.is_clipped(corner));
+    assert_eq!(cut.distance(corner), -1.0);
+    cut.combine = Combine::Union;
+    assert!(!cut.is_clipped(corner));
+    assert_eq!(cut.distance(corner), 2.0);
+    assert!(cut.is_clipped(Vec3::new(-1.0, 0.0, -3.0)));
+}
+
+#[test]
+fn dissolve_moves_from_its_start() {
+    let cut = |start| PlaneCutExt {
+        plane: Vec4::new(0.0, 1.0, 0.0, 0.0),
+        dissolve: Some(Dissolve {
+            amplitude: 0.0,
+            speed: 0.5,
+            start,
+            ..default()
+        }),
+        ..default()
+    };
+    let point = |y, time| CutPoint {
+        time,
+        ..CutPoint::from(Vec3::new(0.0, y, 0.0))
+    };
+    // Put on at 600 s, the plane has only moved by the 2 s since.
+    assert!((cut(600.0).distance(point(2.0, 602.0)) - 1.0).abs() < 1e-4);
+    // The clock wrapped an hour after 3599 s, which is 2 s later.
+    assert!((cut(3599.0).distance(point(2.0, 1.0)) - 1.0).abs() < 1e-4);
+}
+
+#[test]
+fn nplane_cut_only_tests_the_planes_the_device_cuts_with() {
+    let mut planes = vec![(Vec4::new(1.0, 0.0, 0.0, -1.0), Color::BLACK); PLANE_MAX];
+    planes.push((Vec4::new(0.0, 1.0, 0.0, 0.0), Color::BLACK));
+    let cut = NPlaneCutExt {
+        planes_and_colors: planes,
+        ..default()
+    };
+    let below = Vec3::new(0.0, -1.0, 0.0);
+    assert!(cut.is_clipped(below));
+    // Without storage buffers the plane past `PLANE_MAX` is ignored.
+    let point = CutPoint {
+        max_planes: Some(PLANE_MAX),
+        ..CutPoint::from(below)
+    };
+    assert!(!cut.is_clipped(point));
+    assert_eq!(cut.distance(point), 1.0);
+}
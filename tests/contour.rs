@@ -0,0 +1,40 @@
+use bevy::prelude::*;
+use bevy_plane_cut::contour::cross_section;
+use std::f32::consts::PI;
+
+#[test]
+fn torus_has_a_hole() {
+    let mesh = Mesh::from(Torus::new(0.5, 1.0));
+    let transform = GlobalTransform::from_xyz(0.0, 2.0, 0.0);
+    let section = cross_section(&mesh, &transform, Vec4::new(0.0, 1.0, 0.0, 2.0));
+    assert_eq!(section.contours.len(), 2);
+    let hole = section.contours.iter().position(|c| c.is_hole()).unwrap();
+    let outer = 1 - hole;
+    assert_eq!(section.contours[hole].parent, Some(outer));
+    assert_eq!(section.contours[outer].parent, None);
+    assert!(section.contours.iter().all(|c| c.closed));
+    assert!(section.contours[outer]
+        .points
+        .iter()
+        .all(|p| (p.y - 2.0).abs() < 1e-5));
+    // The mesh's circles are polygons, so allow for some error.
+    assert!((section.contours[outer].area() - PI * 1.0 * 1.0).abs() < 0.1);
+    assert!((section.contours[hole].area() - PI * 0.5 * 0.5).abs() < 0.1);
+}
+
+#[test]
+fn open_mesh_has_open_contour() {
+    let mesh = Mesh::from(Plane3d::default().mesh().size(2.0, 2.0));
+    let section = cross_section(
+        &mesh,
+        &GlobalTransform::IDENTITY,
+        Vec4::new(1.0, 0.0, 0.0, 0.25),
+    );
+    assert_eq!(section.contours.len(), 1);
+    let contour = &section.contours[0];
+    assert!(!contour.closed);
+    assert!((contour.length() - 2.0).abs() < 1e-5);
+    for (p, q) in contour.points.iter().zip(&contour.points_2d) {
+        assert!(section.to_world(*q).distance(*p) < 1e-5);
+    }
+}
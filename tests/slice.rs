@@ -0,0 +1,79 @@
+use bevy::{
+    math::{IVec3, Vec3, Vec4},
+    platform::collections::HashMap,
+    prelude::*,
+    render::mesh::VertexAttributeValues,
+};
+use bevy_plane_cut::slice::slice_mesh;
+
+fn triangles(mesh: &Mesh) -> Vec<[Vec3; 3]> {
+    let Some(VertexAttributeValues::Float32x3(positions)) = mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+    else {
+        panic!("no positions");
+    };
+    let indices: Vec<usize> = mesh.indices().unwrap().iter().collect();
+    indices
+        .chunks_exact(3)
+        .map(|t| t.iter().map(|i| Vec3::from(positions[*i])).collect::<Vec<_>>().try_into().unwrap())
+        .collect()
+}
+
+fn volume(mesh: &Mesh) -> f32 {
+    triangles(mesh)
+        .iter()
+        .map(|[a, b, c]| a.dot(b.cross(*c)) / 6.0)
+        .sum()
+}
+
+/// Is every edge matched by an edge in the opposite direction? Positions are
+/// rounded since meshes' seams may not match to the bit.
+fn is_closed(mesh: &Mesh) -> bool {
+    let key = |p: Vec3| (p * 1e4).round().as_ivec3();
+    let mut edges: HashMap<(IVec3, IVec3), i32> = HashMap::default();
+    for [a, b, c] in triangles(mesh) {
+        for (p, q) in [(a, b), (b, c), (c, a)] {
+            *edges.entry((key(p), key(q))).or_default() += 1;
+            *edges.entry((key(q), key(p))).or_default() -= 1;
+        }
+    }
+    edges.values().all(|count| *count == 0)
+}
+
+#[test]
+fn slice_cuboid_in_half() {
+    let mesh = Mesh::from(Cuboid::new(2.0, 2.0, 2.0));
+    let (kept, cut) = slice_mesh(&mesh, Vec4::new(0.0, 1.0, 0.0, 0.25));
+    let (kept, cut) = (kept.unwrap(), cut.unwrap());
+    assert!(is_closed(&kept));
+    assert!(is_closed(&cut));
+    assert!((volume(&kept) - 3.0).abs() < 1e-4);
+    assert!((volume(&cut) - 5.0).abs() < 1e-4);
+}
+
+#[test]
+fn slice_sphere_across_its_seam() {
+    let mesh = Mesh::from(Sphere::new(1.0));
+    let (kept, cut) = slice_mesh(&mesh, Vec4::new(1.0, 0.0, 0.3, 0.1));
+    let (kept, cut) = (kept.unwrap(), cut.unwrap());
+    assert!(is_closed(&kept));
+    assert!(is_closed(&cut));
+    assert!((volume(&kept) + volume(&cut) - volume(&mesh)).abs() < 1e-4);
+}
+
+#[test]
+fn slice_torus_with_a_hole() {
+    let mesh = Mesh::from(Torus::new(0.5, 1.0));
+    let (kept, cut) = slice_mesh(&mesh, Vec4::new(0.0, 1.0, 0.0, 0.0));
+    let (kept, cut) = (kept.unwrap(), cut.unwrap());
+    assert!(is_closed(&kept));
+    assert!(is_closed(&cut));
+    assert!((volume(&kept) - volume(&cut)).abs() < 1e-3);
+}
+
+#[test]
+fn slice_misses() {
+    let mesh = Mesh::from(Cuboid::new(1.0, 1.0, 1.0));
+    let (kept, cut) = slice_mesh(&mesh, Vec4::new(0.0, 1.0, 0.0, -2.0));
+    assert!(kept.is_some());
+    assert!(cut.is_none());
+}